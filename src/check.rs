@@ -0,0 +1,174 @@
+//! Steps shared by `old` and `deprecated`, which only differ in how a single package is checked.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::DebsError;
+use crate::package_json::{
+    get_deps_version, get_non_registry_deps, get_transitive_deps, get_unmet_peer_deps,
+    parse_package_json, Lockfile, NonRegistryPkg,
+};
+use crate::types::{
+    DepCategory, DepsByCategory, PkgName, PkgNameAndVersion, TransitivePkg, UncheckedPkg,
+    UnmetPeerPkg,
+};
+
+/// Dependencies of a workspace, as read from its package.json and the lockfile, before checking them.
+pub struct WorkspaceDeps {
+    /// Direct dependencies to check, by category.
+    pub direct: DepsByCategory<PkgNameAndVersion>,
+    /// Dependencies of dependencies, `None` unless `--transitive` is given.
+    pub transitive: Option<Vec<TransitivePkg>>,
+    pub unmet_peers: Vec<UnmetPeerPkg>,
+    pub non_registry: Vec<NonRegistryPkg>,
+}
+
+impl WorkspaceDeps {
+    /// Every package to query the registry for, direct and transitive ones.
+    pub fn registry_pkgs(&self) -> impl Iterator<Item = &PkgNameAndVersion> {
+        self.direct.values().flatten().chain(
+            self.transitive
+                .iter()
+                .flatten()
+                .map(|TransitivePkg(pkg, _)| pkg),
+        )
+    }
+}
+
+/// Reads the dependencies of `workspace` among `categories`.
+///
+/// Production and development dependencies are always returned when checked, while other categories are
+/// seldom used, so they are only returned when the workspace has some.
+/// Unmet peer dependencies are only looked for when peer dependencies are checked.
+pub fn get_workspace_deps(
+    root: &Path,
+    workspace: &str,
+    lock: &Lockfile,
+    categories: &[DepCategory],
+    include_transitive_packages: bool,
+) -> Result<WorkspaceDeps, DebsError> {
+    // read once, and shared by every step
+    let pkg_json = parse_package_json(&root.join(workspace))?;
+
+    // no need to query the registry for packages that won't be displayed
+    let direct: DepsByCategory<PkgNameAndVersion> = get_deps_version(&pkg_json, workspace, lock)
+        .into_iter()
+        .filter(|(category, deps)| {
+            categories.contains(category)
+                && (matches!(category, DepCategory::Production | DepCategory::Development)
+                    || !deps.is_empty())
+        })
+        .collect();
+
+    let transitive = if include_transitive_packages {
+        let direct: Vec<PkgNameAndVersion> = direct.values().flatten().cloned().collect();
+
+        Some(get_transitive_deps(&pkg_json, workspace, lock, &direct))
+    } else {
+        None
+    };
+
+    let unmet_peers = if categories.contains(&DepCategory::Peer) {
+        get_unmet_peer_deps(&pkg_json, lock)
+    } else {
+        vec![]
+    };
+
+    let non_registry = get_non_registry_deps(&pkg_json, categories);
+
+    Ok(WorkspaceDeps {
+        direct,
+        transitive,
+        unmet_peers,
+        non_registry,
+    })
+}
+
+/// Keeps the transitive packages reported by `check` (`Ok(Some(_))`), along with the direct dependencies
+/// pulling them in, followed by the packages that could not be checked, in the order of `deps`.
+pub fn filter_transitive<T>(
+    deps: &[TransitivePkg],
+    check: impl Fn(&PkgNameAndVersion) -> Result<Option<T>, Arc<DebsError>>,
+) -> (Vec<(T, Vec<PkgName>)>, Vec<UncheckedPkg>) {
+    let mut unchecked = Vec::new();
+
+    let reported = deps
+        .iter()
+        .filter_map(|TransitivePkg(pkg, required_by)| match check(pkg) {
+            Ok(found) => found.map(|found| (found, required_by.clone())),
+            Err(e) => {
+                unchecked.push(UncheckedPkg(pkg.clone(), e));
+                None
+            }
+        })
+        .collect();
+
+    (reported, unchecked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_json::parse_lockfile;
+
+    #[test]
+    fn should_only_return_used_categories() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/monorepo/");
+        let workspaces = ["backend/".to_owned()];
+
+        let lock = parse_lockfile(root, &workspaces)?;
+
+        let deps = get_workspace_deps(root, "backend/", &lock, &DepCategory::ALL, false)?;
+
+        // the workspace has no optional, peer or bundled dependencies
+        assert_eq!(
+            deps.direct.keys().copied().collect::<Vec<_>>(),
+            vec![DepCategory::Production, DepCategory::Development]
+        );
+        assert_eq!(deps.transitive, None);
+
+        let deps = get_workspace_deps(root, "backend/", &lock, &[DepCategory::Production], true)?;
+
+        assert_eq!(
+            deps.direct.keys().copied().collect::<Vec<_>>(),
+            vec![DepCategory::Production]
+        );
+        assert!(deps
+            .transitive
+            .as_ref()
+            .is_some_and(|transitive| !transitive.is_empty()));
+        assert!(deps.registry_pkgs().count() > deps.direct.values().flatten().count());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_filter_transitive_pkgs() {
+        let pkg = |name: &str| PkgNameAndVersion(name.to_owned(), "1.0.0".to_owned());
+
+        let deps = [
+            TransitivePkg(pkg("reported"), vec!["a".to_owned(), "b".to_owned()]),
+            TransitivePkg(pkg("ignored"), vec!["a".to_owned()]),
+            TransitivePkg(pkg("unknown"), vec!["b".to_owned()]),
+        ];
+
+        let (reported, unchecked) =
+            filter_transitive(&deps, |PkgNameAndVersion(name, _)| match name.as_str() {
+                "reported" => Ok(Some(name.clone())),
+                "unknown" => Err(Arc::new(DebsError::PackageNotFound(name.clone()))),
+                _ => Ok(None),
+            });
+
+        assert_eq!(
+            reported,
+            vec![("reported".to_owned(), vec!["a".to_owned(), "b".to_owned()])]
+        );
+        assert_eq!(
+            unchecked,
+            vec![UncheckedPkg(
+                pkg("unknown"),
+                Arc::new(DebsError::PackageNotFound("unknown".to_owned()))
+            )]
+        );
+    }
+}
@@ -2,82 +2,307 @@
 //!
 //! The entry point to this module is [deprecated].
 //!
-//! We can determine deprecation for a package version with
-//! `GET https://registry.npmjs.org/:package/:version` (see [npm registry] for details).
-//! Note that we pass the package name along its version, which we grab from the current workspace's package.json.
+//! We can determine deprecation for every version of a package at once with
+//! `GET <registry>/:package`, requesting the [abbreviated metadata] format
+//! with the `Accept: application/vnd.npm.install-v1+json` header (see [npm registry] for details).
+//! Each package is fetched once, no matter how many workspaces or versions use it,
+//! and the versions we grab from the workspaces' package.json are then looked up in the document's `versions` field.
 //!
-//! The resulting JSON version object comes with the `deprecated` field, which is in the
+//! Each version object comes with the `deprecated` field, which is in the
 //! following formats:
 //!
 //! - string: a deprecation message usually specifying which version to update to, but useless for our means
 //! - boolean: which directly tells us if the package is deprecated or not
 //! - undefined: this is the normal case for non-deprecated packages
 //!
+//! [abbreviated metadata]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md#abbreviated-metadata-format
 //! [npm registry]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md
 
-use std::error::Error;
+use std::collections::HashMap;
+use std::io::Write;
 use std::path::Path;
+use std::sync::Arc;
 
 use futures::future;
+use serde::Serialize;
+
+use crate::check::{filter_transitive, get_workspace_deps, WorkspaceDeps};
+use crate::error::DebsError;
+use crate::package_json::Lockfile;
+use crate::registry::{
+    pkg_abbreviated_info, AbbreviatedMetadata, DeprecatedField, Registry, VersionObject,
+};
+use crate::report::text::{
+    get_non_registry_output, get_total, get_unchecked_output, get_unmet_peers_output,
+};
+use crate::report::{write_report, Check, Field, Finding, Format, WorkspaceResults};
+use crate::types::{
+    DepCategory, DepsByCategory, PkgName, PkgNameAndVersion, UncheckedPkg, Version,
+};
+
+/// Abbreviated metadata documents (or the error met while fetching them), by package name.
+type Documents = HashMap<PkgName, Result<AbbreviatedMetadata, Arc<DebsError>>>;
+
+/// Deprecated transitive package, along with the direct dependencies pulling it in.
+type TransitiveDeprecatedPkg = (VersionObject, Vec<PkgName>);
+
+/// Details of a deprecated package, as serialized in the JSON output (see [`crate::report`]).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct DeprecatedPkgDetails {
+    pub name: PkgName,
+    pub local_version: Version,
+    /// `None` when the version is only flagged as deprecated, without a message.
+    pub deprecation_message: Option<String>,
+}
+
+impl From<VersionObject> for DeprecatedPkgDetails {
+    fn from(
+        VersionObject {
+            name,
+            version,
+            deprecated,
+        }: VersionObject,
+    ) -> Self {
+        DeprecatedPkgDetails {
+            name,
+            local_version: version,
+            deprecation_message: match deprecated {
+                Some(DeprecatedField::String(message)) => Some(message),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl Finding for DeprecatedPkgDetails {
+    const COLUMNS: &'static [&'static str] = &["Deprecation message"];
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn local_version(&self) -> &str {
+        &self.local_version
+    }
+
+    fn description(&self) -> String {
+        match &self.deprecation_message {
+            Some(message) => format!(
+                "{}@{} is deprecated: {message}",
+                self.name, self.local_version
+            ),
+            None => format!("{}@{} is deprecated", self.name, self.local_version),
+        }
+    }
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.deprecation_message.clone().unwrap_or_default()]
+    }
 
-use crate::package_json::get_deps_version;
-use crate::registry::{pkg_version_info, DeprecatedField, VersionObject};
-use crate::types::PkgNameAndVersion;
+    const FIELDS: &'static [Field<Self>] = &[("deprecation_message", |pkg| {
+        pkg.deprecation_message.clone().unwrap_or_default()
+    })];
+}
 
-/// Takes workspace paths and returns a string describing deprecated packages from these workspaces.
+/// Takes workspace paths and returns a string describing deprecated packages from these workspaces in the given format,
+/// along with the packages that could not be checked (also listed in the string).
+///
+/// Fails when the package.json of a workspace cannot be read.
 ///
 /// ## Parameters
 ///
 /// | Parameter | Description |
 /// | --------- | ----------- |
-/// | **prod_pkgs_only:** | Ignore development dependencies. |
+/// | **registry:**       | Registry to fetch abbreviated metadata from. |
+/// | **categories:**     | Categories of direct dependencies to check. Unmet peer dependencies are listed when peer dependencies are checked. |
+/// | **transitive:**     | Also check the dependencies of dependencies, see [`crate::package_json::get_transitive_deps`]. |
 /// | **path:**           | Path to the root package.json containing workspace names. |
 /// | **workspaces:**     | Workspaces to check installed dependencies and versions from. |
+/// | **lock:**           | Root lockfile, listing the installed versions. |
+/// | **format:**         | Format of the output. |
 pub async fn get_deprecated_packages(
+    registry: &Registry,
     path: &Path,
     workspaces: &[String],
-    include_development_packages: bool,
-) -> String {
-    let deps_by_workspace: Vec<(Vec<PkgNameAndVersion>, Vec<PkgNameAndVersion>)> = workspaces
+    lock: &Lockfile,
+    categories: &[DepCategory],
+    include_transitive_packages: bool,
+    format: Format,
+) -> Result<(String, Vec<UncheckedPkg>), DebsError> {
+    let deps_by_workspace = workspaces
         .iter()
-        .flat_map(|workspace| {
-            get_deps_version(&path.join(workspace), path, workspace == "frontend")
+        .map(|workspace| {
+            get_workspace_deps(
+                path,
+                workspace,
+                lock,
+                categories,
+                include_transitive_packages,
+            )
         })
-        .collect();
+        .collect::<Result<Vec<_>, DebsError>>()?;
 
-    // Wait for all deps to be tested for deprecation, and zip them together with their workspace name
-    let deprecated_deps_by_workspace = future::join_all(
+    // Fetch every package used in any workspace once
+    let documents = fetch_documents(
+        registry,
         deps_by_workspace
-            .into_iter()
-            .map(|(prod, dev)| filter_deprecated_packages((prod, dev))),
+            .iter()
+            .flat_map(WorkspaceDeps::registry_pkgs),
     )
-    .await
-    .into_iter()
-    // Since errors were already "flattened" inside of `get_deprecated_deps` we can safely unwrap here
-    .map(|version_objs| version_objs.unwrap())
-    .zip(workspaces);
+    .await;
 
-    deprecated_deps_by_workspace
+    let results: Vec<WorkspaceResults<VersionObject>> = deps_by_workspace
         .into_iter()
-        .fold(String::new(), |acc, ((prod, dev), workspace)| {
-            let output = get_output((&prod, &dev), include_development_packages);
-            format!("{acc}\n[{workspace}] deprecated packages:\n{output}")
+        .zip(workspaces)
+        .map(
+            |(
+                WorkspaceDeps {
+                    direct,
+                    transitive,
+                    unmet_peers,
+                    non_registry,
+                },
+                workspace,
+            )| {
+                let (deprecated, mut unchecked) = filter_deprecated_packages(&documents, &direct);
+
+                let transitive_deprecated = transitive.as_ref().map(|transitive| {
+                    let (transitive_deprecated, transitive_unchecked) =
+                        filter_transitive(transitive, |pkg| deprecation(&documents, pkg));
+
+                    unchecked.extend(transitive_unchecked);
+
+                    transitive_deprecated
+                });
+
+                WorkspaceResults {
+                    workspace: workspace.clone(),
+                    direct: deprecated,
+                    transitive: transitive_deprecated,
+                    unmet_peers,
+                    non_registry,
+                    unchecked,
+                    checked: direct,
+                    checked_transitive: transitive,
+                }
+            },
+        )
+        .collect();
+
+    let unchecked = results
+        .iter()
+        .flat_map(|results| results.unchecked.iter().cloned())
+        .collect();
+
+    let details: Vec<WorkspaceResults<DeprecatedPkgDetails>> = results
+        .iter()
+        .cloned()
+        .map(|results| results.map(DeprecatedPkgDetails::from))
+        .collect();
+
+    let mut bytes = Vec::new();
+    write_report(
+        format,
+        Check::Deprecated,
+        path,
+        &details,
+        &mut bytes,
+        |writer| {
+            for results in &results {
+                let output = get_output(&results.direct, results.transitive.as_deref());
+                let unmet_peers_output = get_unmet_peers_output(&results.unmet_peers);
+                let non_registry_output = get_non_registry_output(&results.non_registry);
+                let unchecked_output = get_unchecked_output(&results.unchecked);
+
+                writeln!(writer, "\n[{}] deprecated packages:", results.workspace)?;
+                write!(
+                    writer,
+                    "{output}{unmet_peers_output}{non_registry_output}{unchecked_output}"
+                )?;
+            }
+
+            Ok(())
+        },
+    )?;
+
+    Ok((String::from_utf8_lossy(&bytes).into_owned(), unchecked))
+}
+
+/// Fetches the abbreviated metadata of each distinct package among `deps`, concurrently.
+async fn fetch_documents<'a>(
+    registry: &Registry,
+    deps: impl Iterator<Item = &'a PkgNameAndVersion>,
+) -> Documents {
+    let mut pkg_names: Vec<&PkgName> = deps.map(|PkgNameAndVersion(name, _)| name).collect();
+
+    pkg_names.sort_unstable();
+    pkg_names.dedup();
+
+    let documents = future::join_all(
+        pkg_names
+            .iter()
+            .map(|name| pkg_abbreviated_info(registry, name)),
+    )
+    .await;
+
+    pkg_names
+        .into_iter()
+        .cloned()
+        .zip(
+            documents
+                .into_iter()
+                .map(|document| document.map_err(Arc::new)),
+        )
+        .collect()
+}
+
+/// Return deprecated packages of each category, followed by the packages that could not be checked.
+///
+/// Every category of `deps` is returned, even without deprecated packages.
+fn filter_deprecated_packages(
+    documents: &Documents,
+    deps: &DepsByCategory<PkgNameAndVersion>,
+) -> (DepsByCategory<VersionObject>, Vec<UncheckedPkg>) {
+    let mut unchecked = Vec::new();
+
+    let deprecated = deps
+        .iter()
+        .map(|(category, deps)| {
+            let (deprecated, category_unchecked) = filter_deprecated(documents, deps);
+
+            unchecked.extend(category_unchecked);
+
+            (*category, deprecated)
         })
+        .collect();
+
+    (deprecated, unchecked)
 }
 
-/// Return a tuple containing deprecated production & development packages.
+/// Filters deprecated packages, reading their version object from the previously fetched `documents`.
 ///
-/// Fails if the filter operation fails for either production or development deps.
-async fn filter_deprecated_packages(
-    (prod_deps, dev_deps): (Vec<PkgNameAndVersion>, Vec<PkgNameAndVersion>),
-) -> Result<(Vec<VersionObject>, Vec<VersionObject>), Box<dyn Error>> {
-    Ok((
-        filter_deprecated(&prod_deps).await?,
-        filter_deprecated(&dev_deps).await?,
-    ))
+/// Packages whose document could not be fetched, or whose version is missing from it,
+/// are returned separately with the reason why they could not be checked.
+fn filter_deprecated(
+    documents: &Documents,
+    deps: &[PkgNameAndVersion],
+) -> (Vec<VersionObject>, Vec<UncheckedPkg>) {
+    let mut unchecked = Vec::new();
+
+    let deprecated = deps
+        .iter()
+        .filter_map(|pkg| {
+            deprecation(documents, pkg)
+                .map_err(|e| unchecked.push(UncheckedPkg(pkg.clone(), e)))
+                .ok()
+                .flatten()
+        })
+        .collect();
+
+    (deprecated, unchecked)
 }
 
-/// Filters deprecated packages. Ignores errors that may occur while fetching version info.
+/// Version object of `pkg` when it is deprecated, read from the previously fetched `documents`.
 ///
 /// Assessing whether a package version is deprecated requires reading the `deprecated` field from the package info:
 ///
@@ -85,53 +310,63 @@ async fn filter_deprecated_packages(
 /// - if the field is exists:
 ///     - and is a boolean, `true` would mean the package is deprecated
 ///     - and is a string, the package is deprecated
-async fn filter_deprecated(
-    deps: &[PkgNameAndVersion],
-) -> Result<Vec<VersionObject>, Box<dyn Error>> {
-    let abbr_version_objects: Vec<Result<VersionObject, Box<dyn Error>>> =
-        future::join_all(deps.iter().map(|PkgNameAndVersion(pkg_name, version)| {
-            pkg_version_info(pkg_name.as_str(), version.as_str())
-        }))
-        .await;
-
-    Ok(abbr_version_objects
-        .into_iter()
-        .flatten()
-        .filter(|vo| match &vo.deprecated {
-            Some(depr_field) => match depr_field {
-                DeprecatedField::String(_) => true,
-                DeprecatedField::Bool(b) => *b,
-            },
-            None => false,
-        })
-        .collect())
+fn deprecation(
+    documents: &Documents,
+    pkg: &PkgNameAndVersion,
+) -> Result<Option<VersionObject>, Arc<DebsError>> {
+    let PkgNameAndVersion(name, version) = pkg;
+
+    let vo = match documents.get(name).expect("every package is fetched") {
+        Ok(document) => document
+            .versions
+            .get(version)
+            .cloned()
+            .ok_or_else(|| Arc::new(DebsError::VersionNotFound(pkg.clone())))?,
+        Err(e) => return Err(e.clone()),
+    };
+
+    let is_deprecated = match &vo.deprecated {
+        Some(depr_field) => match depr_field {
+            DeprecatedField::String(_) => true,
+            DeprecatedField::Bool(b) => *b,
+        },
+        None => false,
+    };
+
+    Ok(is_deprecated.then_some(vo))
 }
 
 /// Returns the entire output for the deprecated task, including workspace headers
 /// and statistics.
+///
+/// Each category of `deprecated` is shown (and counted), even without deprecated packages.
+/// Transitive packages are only shown (and counted) in `--transitive` mode, when given.
 fn get_output(
-    (prod_deprecated, dev_deprecated): (&[VersionObject], &[VersionObject]),
-    include_development_packages: bool,
+    deprecated: &DepsByCategory<VersionObject>,
+    transitive_deprecated: Option<&[TransitiveDeprecatedPkg]>,
 ) -> String {
-    if include_development_packages {
-        let res_prod = get_pkgs_output(prod_deprecated, Some("production:"));
-        let res_dev = get_pkgs_output(dev_deprecated, Some("development:"));
-
-        let num_depr_prods = prod_deprecated.len();
-        let num_depr_devs = dev_deprecated.len();
-
-        format!(
-        "{res_prod}{res_dev}\n  total: {num_depr_prods} deprecated dependenc{end_1}, {num_depr_devs} deprecated dev dependenc{end_2}\n",
-        end_1 = if num_depr_prods == 1 { "y" } else { "ies" },
-        end_2 = if num_depr_devs == 1 { "y" } else { "ies" },
-      )
-    } else {
-        let res = get_pkgs_output(prod_deprecated, None);
+    let res_transitive = transitive_deprecated
+        .map(get_transitive_pkgs_output)
+        .unwrap_or_default();
 
-        let num_depr_pkgs = prod_deprecated.len();
+    // production packages need a header to be told apart from other categories, and from transitive ones
+    let with_headers = transitive_deprecated.is_some()
+        || deprecated
+            .keys()
+            .any(|category| *category != DepCategory::Production);
 
-        format!("{res}\n  total: {num_depr_pkgs} deprecated production dependencies\n")
-    }
+    let res: String = deprecated
+        .iter()
+        .map(|(category, pkgs)| get_pkgs_output(pkgs, with_headers.then(|| category.header())))
+        .collect();
+
+    let total = get_total(
+        deprecated,
+        transitive_deprecated.map(<[_]>::len),
+        "deprecated",
+    );
+
+    format!("{res}{res_transitive}\n  total: {total}\n")
 }
 
 /// Returns output for packages only, without headers or statistics.
@@ -164,11 +399,38 @@ fn get_pkgs_output(pkgs: &[VersionObject], tag_line: Option<&str>) -> String {
     res
 }
 
+/// Returns output for transitive packages under the "transitive:" header,
+/// mentioning the direct dependencies pulling each of them in.
+fn get_transitive_pkgs_output(pkgs: &[TransitiveDeprecatedPkg]) -> String {
+    if pkgs.is_empty() {
+        return String::new();
+    }
+
+    let mut res = "\n  transitive:\n".to_owned();
+
+    for (VersionObject { name, version, .. }, required_by) in pkgs {
+        res.push_str(
+            format!(
+                "\n    {name}@{version} (required by {})",
+                required_by.join(", ")
+            )
+            .as_str(),
+        );
+    }
+
+    res.push('\n');
+    res
+}
+
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
+
     use once_cell::sync::Lazy;
 
     use super::*;
+    use crate::package_json::parse_lockfile;
+    use crate::types::TransitivePkg;
 
     static DEPR_PKG_DETAILS: Lazy<Vec<VersionObject>> = Lazy::new(|| {
         vec![
@@ -200,13 +462,23 @@ mod tests {
             "frontend/".to_owned(),
         ];
 
-        let output = get_deprecated_packages(path, &workspaces, true).await;
+        let (output, _) = get_deprecated_packages(
+            &Registry::default(),
+            path,
+            &workspaces,
+            &parse_lockfile(path, &workspaces)?,
+            &DepCategory::ALL,
+            false,
+            Format::Text,
+        )
+        .await?;
 
         assert!(output.contains("backend/"));
         assert!(output.contains("common/"));
         assert!(output.contains("frontend/"));
-        assert!(output.contains("@babel/polyfill@7.12.1"));
-        assert!(output.contains("noty@3.2.0-beta-deprecated"));
+        // listed as deprecated, not as "could not check: <reason>"
+        assert!(output.contains("@babel/polyfill@7.12.1\n"));
+        assert!(output.contains("noty@3.2.0-beta-deprecated\n"));
 
         Ok(())
     }
@@ -221,13 +493,17 @@ mod tests {
             "file-loader".to_owned(),
             "1.1.11".to_owned(),
         )];
-        let maybe_deprecated = filter_deprecated_packages((prod, devs)).await;
-
-        assert!(maybe_deprecated.is_ok());
+        let documents = fetch_documents(&Registry::default(), prod.iter().chain(devs.iter())).await;
 
-        let (prod_depr, dev_depr) = maybe_deprecated?;
+        let (deprecated, _) = filter_deprecated_packages(
+            &documents,
+            &DepsByCategory::from([
+                (DepCategory::Production, prod),
+                (DepCategory::Development, devs),
+            ]),
+        );
 
-        assert!(prod_depr.iter().any(
+        assert!(deprecated[&DepCategory::Production].iter().any(
             |VersionObject {
                  name,
                  version,
@@ -237,7 +513,7 @@ mod tests {
                 && deprecated.is_some()
         ));
 
-        assert!(dev_depr.is_empty());
+        assert!(deprecated[&DepCategory::Development].is_empty());
 
         Ok(())
     }
@@ -246,15 +522,118 @@ mod tests {
     fn get_deprecated_output_test() {
         let (in1, in2) = (DEPR_PKG_DETAILS.to_vec(), DEPR_PKG_DETAILS.to_vec());
 
-        let output = get_output((&in1, &in2), true);
+        let output = get_output(
+            &DepsByCategory::from([
+                (DepCategory::Production, in1.clone()),
+                (DepCategory::Development, in2),
+            ]),
+            None,
+        );
 
         assert_eq!(output, "\n  production:\n\n    depr1@0.0.1\n    depr2@0.0.1\n    not_depr@0.0.1\n\n  development:\n\n    depr1@0.0.1\n    depr2@0.0.1\n    not_depr@0.0.1\n\n  total: 3 deprecated dependencies, 3 deprecated dev dependencies\n");
 
-        let output = get_output((&in1, &in2), false);
+        let output = get_output(
+            &DepsByCategory::from([(DepCategory::Production, in1)]),
+            None,
+        );
 
         assert_eq!(output, "\n  depr1@0.0.1\n  depr2@0.0.1\n  not_depr@0.0.1\n\n  total: 3 deprecated production dependencies\n");
     }
 
+    #[test]
+    fn get_deprecated_transitive_output_test() {
+        let transitive = vec![(
+            DEPR_PKG_DETAILS[0].clone(),
+            vec!["a".to_owned(), "b".to_owned()],
+        )];
+
+        let output = get_output(
+            &DepsByCategory::from([(DepCategory::Production, DEPR_PKG_DETAILS[1..2].to_vec())]),
+            Some(&transitive),
+        );
+
+        assert_eq!(output, "\n  production:\n\n    depr2@0.0.1\n\n  transitive:\n\n    depr1@0.0.1 (required by a, b)\n\n  total: 1 deprecated production dependencies, 1 deprecated transitive dependency\n");
+    }
+
+    #[test]
+    fn get_deprecated_output_of_every_category_test() {
+        let output = get_output(
+            &DepsByCategory::from([
+                (DepCategory::Production, vec![]),
+                (DepCategory::Peer, DEPR_PKG_DETAILS[..1].to_vec()),
+                (DepCategory::Bundled, vec![]),
+            ]),
+            None,
+        );
+
+        assert_eq!(output, "\n  peer:\n\n    depr1@0.0.1\n\n  total: 0 deprecated production dependencies, 1 deprecated peer dependency, 0 deprecated bundled dependencies\n");
+    }
+
+    #[test]
+    fn should_keep_deprecation_messages_only() {
+        let details: Vec<DeprecatedPkgDetails> = DEPR_PKG_DETAILS[..2]
+            .iter()
+            .cloned()
+            .map(DeprecatedPkgDetails::from)
+            .collect();
+
+        assert_eq!(
+            details,
+            vec![
+                DeprecatedPkgDetails {
+                    name: "depr1".to_owned(),
+                    local_version: "0.0.1".to_owned(),
+                    deprecation_message: Some("AAAAAAAAAAAAAAAAAA".to_owned()),
+                },
+                DeprecatedPkgDetails {
+                    name: "depr2".to_owned(),
+                    local_version: "0.0.1".to_owned(),
+                    deprecation_message: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_filter_deprecated_transitive_pkgs() {
+        let documents = Documents::from([
+            (
+                "depr1".to_owned(),
+                Ok(AbbreviatedMetadata {
+                    name: "depr1".to_owned(),
+                    versions: HashMap::from([("0.0.1".to_owned(), DEPR_PKG_DETAILS[0].clone())]),
+                }),
+            ),
+            (
+                "other".to_owned(),
+                Err(Arc::new(DebsError::PackageNotFound("other".to_owned()))),
+            ),
+        ]);
+
+        let pkg = PkgNameAndVersion("depr1".to_owned(), "0.0.1".to_owned());
+        let unknown = PkgNameAndVersion("other".to_owned(), "1.0.0".to_owned());
+
+        let (deprecated, unchecked) = filter_transitive(
+            &[
+                TransitivePkg(pkg, vec!["a".to_owned()]),
+                TransitivePkg(unknown.clone(), vec!["a".to_owned()]),
+            ],
+            |pkg| deprecation(&documents, pkg),
+        );
+
+        assert_eq!(
+            deprecated,
+            vec![(DEPR_PKG_DETAILS[0].clone(), vec!["a".to_owned()])]
+        );
+        assert_eq!(
+            unchecked,
+            vec![UncheckedPkg(
+                unknown,
+                Arc::new(DebsError::PackageNotFound("other".to_owned()))
+            )]
+        );
+    }
+
     #[test]
     fn format_deprecated_output_test() {
         let output = get_pkgs_output(DEPR_PKG_DETAILS.as_slice(), None);
@@ -262,11 +641,97 @@ mod tests {
         assert_eq!(output, "\n  depr1@0.0.1\n  depr2@0.0.1\n  not_depr@0.0.1\n");
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_report_pkgs_missing_from_offline_cache() -> Result<(), Box<dyn Error>> {
+        // offline without a cache: nothing can be checked
+        let registry = Registry::default().with_offline(true);
+
+        let deps = vec![PkgNameAndVersion("core-js".to_owned(), "3.19.0".to_owned())];
+
+        let documents = fetch_documents(&registry, deps.iter()).await;
+
+        let (deprecated, unchecked) = filter_deprecated(&documents, &deps);
+
+        assert!(deprecated.is_empty());
+        assert_eq!(
+            unchecked,
+            vec![UncheckedPkg(
+                deps[0].clone(),
+                Arc::new(DebsError::NotInCache("core-js".to_owned()))
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_resolve_every_version_from_a_single_document() {
+        let document = AbbreviatedMetadata {
+            name: "depr".to_owned(),
+            versions: DEPR_PKG_DETAILS
+                .iter()
+                .zip(["1.0.1", "1.0.2", "1.0.3"])
+                .map(|(vo, version)| {
+                    (
+                        version.to_owned(),
+                        VersionObject {
+                            name: "depr".to_owned(),
+                            version: version.to_owned(),
+                            deprecated: vo.deprecated.clone(),
+                        },
+                    )
+                })
+                .collect(),
+        };
+
+        let documents = Documents::from([
+            ("depr".to_owned(), Ok(document)),
+            (
+                "other".to_owned(),
+                Err(Arc::new(DebsError::PackageNotFound("other".to_owned()))),
+            ),
+        ]);
+
+        let deps = vec![
+            PkgNameAndVersion("depr".to_owned(), "1.0.1".to_owned()),
+            PkgNameAndVersion("depr".to_owned(), "1.0.2".to_owned()),
+            PkgNameAndVersion("depr".to_owned(), "1.0.3".to_owned()),
+            // missing from the document, and unknown to the registry
+            PkgNameAndVersion("depr".to_owned(), "A.0.0".to_owned()),
+            PkgNameAndVersion("other".to_owned(), "1.0.0".to_owned()),
+        ];
+
+        let (deprecated, unchecked) = filter_deprecated(&documents, &deps);
+
+        assert_eq!(
+            deprecated
+                .iter()
+                .map(|vo| vo.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1.0.1", "1.0.2"]
+        );
+        assert_eq!(
+            unchecked,
+            vec![
+                UncheckedPkg(
+                    deps[3].clone(),
+                    Arc::new(DebsError::VersionNotFound(deps[3].clone()))
+                ),
+                UncheckedPkg(
+                    deps[4].clone(),
+                    Arc::new(DebsError::PackageNotFound("other".to_owned()))
+                ),
+            ]
+        );
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn should_filter_deprecated_pkgs() -> Result<(), Box<dyn Error>> {
         let deps = vec![PkgNameAndVersion("core-js".to_owned(), "3.19.0".to_owned())];
 
-        let res = filter_deprecated(&deps).await?;
+        let documents = fetch_documents(&Registry::default(), deps.iter()).await;
+
+        let (res, _) = filter_deprecated(&documents, &deps);
 
         let expected = VersionObject {
             name: "core-js".to_owned(),
@@ -283,11 +748,13 @@ mod tests {
     async fn should_filter_out_fake_pkg() -> Result<(), Box<dyn Error>> {
         let deps = vec![PkgNameAndVersion("fake-js".to_owned(), "3.19.0".to_owned())];
 
-        let res = filter_deprecated(&deps).await;
+        let documents = fetch_documents(&Registry::default(), deps.iter()).await;
+
+        let (res, unchecked) = filter_deprecated(&documents, &deps);
 
-        assert!(res.is_ok());
+        assert!(res.is_empty());
 
-        assert!(res.unwrap().is_empty());
+        assert_eq!(unchecked.len(), 1);
 
         Ok(())
     }
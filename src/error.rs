@@ -0,0 +1,319 @@
+//! Errors met while reading a project or querying the registry.
+//!
+//! Errors about a single package (e.g. [`DebsError::VersionNotFound`]) are reported alongside the results,
+//! in the "could not check" section. The others stop debs, which exits with [`DebsError::exit_code`].
+
+use std::fmt;
+use std::path::PathBuf;
+
+use reqwest::StatusCode;
+
+use crate::types::{PkgName, PkgNameAndVersion, UncheckedPkg};
+
+#[derive(Debug)]
+pub enum DebsError {
+    /// A file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A file is not valid JSON, or does not have the expected shape.
+    JsonParse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A file is not valid YAML (e.g. yarn.lock from Yarn Berry), or does not have the expected shape.
+    YamlParse {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The output could not be written (e.g. closed pipe).
+    Output(std::io::Error),
+    /// The registry answered with an unsuccessful status other than `404 Not Found`.
+    RegistryHttp { pkg: PkgName, status: StatusCode },
+    /// The registry could not be reached, or its response could not be read.
+    Network {
+        pkg: PkgName,
+        source: reqwest::Error,
+    },
+    /// The registry answered with a document debs does not understand.
+    InvalidMetadata {
+        pkg: PkgName,
+        source: serde_json::Error,
+    },
+    /// In offline mode, the response for a package was never cached.
+    NotInCache(PkgName),
+    /// The registry does not know the package.
+    PackageNotFound(PkgName),
+    /// The registry knows the package, but not the version installed in the project.
+    VersionNotFound(PkgNameAndVersion),
+    /// A publication date from the registry is not in the RFC 3339 format, or is in the future.
+    InvalidDate { pkg: PkgName, date: String },
+    /// The registry metadata lacks a field debs needs, e.g. `dist-tags.latest` for unpublished packages.
+    MissingMetadata { pkg: PkgName, field: String },
+    /// The project lists workspaces (in package.json or pnpm-workspace.yaml), but none of them exist.
+    MissingWorkspaces(PathBuf),
+    /// Some packages could not be checked, and `--fail-on-errors` was given.
+    UncheckedPackages(Vec<UncheckedPkg>),
+    /// `verify-lock` found installed versions not satisfying the ranges declared in package.json.
+    StaleLockfile(usize),
+}
+
+impl DebsError {
+    /// Wraps a `serde_json` error met while parsing the file at `path`.
+    pub fn json_parse(path: PathBuf, e: serde_json::Error) -> Self {
+        let message = e.to_string();
+
+        // `serde_json` appends the position to its messages, which we report on our own
+        let message = match message.rfind(" at line ") {
+            Some(i) => message[..i].to_owned(),
+            None => message,
+        };
+
+        DebsError::JsonParse {
+            path,
+            line: e.line(),
+            column: e.column(),
+            message,
+        }
+    }
+
+    /// Wraps a `serde_yaml` error met while parsing the file at `path`.
+    pub fn yaml_parse(path: PathBuf, e: serde_yaml::Error) -> Self {
+        let (line, column) = e
+            .location()
+            .map_or((0, 0), |location| (location.line(), location.column()));
+
+        let message = e.to_string();
+
+        // like `serde_json`, `serde_yaml` appends the position to its messages
+        let message = match message.rfind(" at line ") {
+            Some(i) => message[..i].to_owned(),
+            None => message,
+        };
+
+        DebsError::YamlParse {
+            path,
+            line,
+            column,
+            message,
+        }
+    }
+
+    /// Describes the error without naming the package it is about,
+    /// for lists which already name each package (e.g. "could not check").
+    pub fn reason(&self) -> String {
+        match self {
+            DebsError::RegistryHttp { status, .. } => format!("registry responded with {status}"),
+            DebsError::Network { source, .. } => source.to_string(),
+            DebsError::InvalidMetadata { source, .. } => {
+                format!("invalid registry response: {source}")
+            }
+            DebsError::NotInCache(_) => "not found in the cache (offline)".to_owned(),
+            DebsError::PackageNotFound(_) => "not found in the registry".to_owned(),
+            DebsError::VersionNotFound(_) => "version not found in the registry".to_owned(),
+            DebsError::InvalidDate { date, .. } => format!("invalid publication date \"{date}\""),
+            DebsError::MissingMetadata { field, .. } => {
+                format!("no \"{field}\" in the registry metadata")
+            }
+            _ => self.to_string(),
+        }
+    }
+
+    /// Process exit code for this error, one per category:
+    ///
+    /// | Code | Category |
+    /// | ---- | -------- |
+    /// | 1 | some packages could not be checked for different reasons (`--fail-on-errors`) |
+    /// | 3 | a file could not be read, or the output could not be written |
+    /// | 4 | a file is not valid JSON or YAML |
+    /// | 5 | the project has no workspaces |
+    /// | 6 | the registry could not be queried |
+    /// | 7 | a package or version is unknown to the registry |
+    /// | 8 | the registry data is invalid |
+    /// | 9 | the lockfile doesn't satisfy the declared ranges (`verify-lock`) |
+    ///
+    /// `2` is used by clap for invalid command line arguments.
+    ///
+    /// With `--fail-on-errors`, packages that could not be checked for the same reason (e.g. all missing from
+    /// the registry) exit with the code of that reason.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            DebsError::UncheckedPackages(pkgs) => {
+                let mut codes = pkgs.iter().map(|UncheckedPkg(_, e)| e.exit_code());
+
+                match codes.next() {
+                    Some(code) if codes.all(|other| other == code) => code,
+                    _ => 1,
+                }
+            }
+            DebsError::Io { .. } | DebsError::Output(_) => 3,
+            DebsError::JsonParse { .. } | DebsError::YamlParse { .. } => 4,
+            DebsError::MissingWorkspaces(_) => 5,
+            DebsError::RegistryHttp { .. }
+            | DebsError::Network { .. }
+            | DebsError::NotInCache(_) => 6,
+            DebsError::PackageNotFound(_) | DebsError::VersionNotFound(_) => 7,
+            DebsError::InvalidMetadata { .. }
+            | DebsError::InvalidDate { .. }
+            | DebsError::MissingMetadata { .. } => 8,
+            DebsError::StaleLockfile(_) => 9,
+        }
+    }
+}
+
+impl fmt::Display for DebsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebsError::Io { path, source } => {
+                write!(f, "could not read {}: {source}", path.display())
+            }
+            DebsError::JsonParse {
+                path,
+                line,
+                column,
+                message,
+            }
+            | DebsError::YamlParse {
+                path,
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{line}:{column}: {message}", path.display()),
+            DebsError::Output(e) => write!(f, "could not write the output: {e}"),
+            DebsError::RegistryHttp { pkg, .. }
+            | DebsError::Network { pkg, .. }
+            | DebsError::InvalidMetadata { pkg, .. }
+            | DebsError::NotInCache(pkg)
+            | DebsError::PackageNotFound(pkg)
+            | DebsError::InvalidDate { pkg, .. }
+            | DebsError::MissingMetadata { pkg, .. } => write!(f, "{pkg}: {}", self.reason()),
+            DebsError::VersionNotFound(PkgNameAndVersion(pkg, version)) => {
+                write!(f, "{pkg}@{version}: {}", self.reason())
+            }
+            DebsError::MissingWorkspaces(path) => write!(
+                f,
+                "no workspaces found in {}. Cannot read the path to the necessary dependency information.",
+                path.display()
+            ),
+            DebsError::UncheckedPackages(pkgs) => {
+                write!(f, "{} package(s) could not be checked", pkgs.len())
+            }
+            DebsError::StaleLockfile(count) => {
+                write!(
+                    f,
+                    "{count} dependency range(s) not satisfied by the lockfile"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for DebsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DebsError::Io { source, .. } | DebsError::Output(source) => Some(source),
+            DebsError::Network { source, .. } => Some(source),
+            DebsError::InvalidMetadata { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Lets `?` report failed writes to the output.
+impl From<std::io::Error> for DebsError {
+    fn from(e: std::io::Error) -> Self {
+        DebsError::Output(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn should_report_json_errors_with_their_position() {
+        let e = serde_json::from_str::<serde_json::Value>("{\n  \"a\": 1,\n}").unwrap_err();
+
+        let e = DebsError::json_parse(PathBuf::from("package.json"), e);
+
+        assert!(matches!(
+            e,
+            DebsError::JsonParse {
+                line: 3,
+                column: 1,
+                ..
+            }
+        ));
+        assert_eq!(e.to_string(), "package.json:3:1: trailing comma");
+    }
+
+    #[test]
+    fn should_report_yaml_errors_with_their_position() {
+        let e = serde_yaml::from_str::<serde_yaml::Value>("a: 1\nb: [\n").unwrap_err();
+
+        let e = DebsError::yaml_parse(PathBuf::from("yarn.lock"), e);
+
+        assert_eq!(e.exit_code(), 4);
+        assert!(e.to_string().starts_with("yarn.lock:3:1: "));
+    }
+
+    #[test]
+    fn should_describe_pkg_errors_without_their_name() {
+        let e = DebsError::VersionNotFound(PkgNameAndVersion(
+            "left-pad".to_owned(),
+            "9.9.9".to_owned(),
+        ));
+
+        assert_eq!(e.reason(), "version not found in the registry");
+        assert_eq!(
+            e.to_string(),
+            "left-pad@9.9.9: version not found in the registry"
+        );
+        assert_eq!(
+            DebsError::NotInCache("react".to_owned()).to_string(),
+            "react: not found in the cache (offline)"
+        );
+    }
+
+    #[test]
+    fn should_map_errors_to_distinct_exit_codes() {
+        let io = DebsError::Io {
+            path: PathBuf::from("package-lock.json"),
+            source: std::io::ErrorKind::NotFound.into(),
+        };
+
+        assert_eq!(io.exit_code(), 3);
+        assert_eq!(
+            DebsError::MissingWorkspaces(PathBuf::from("package.json")).exit_code(),
+            5
+        );
+        assert_eq!(DebsError::NotInCache("react".to_owned()).exit_code(), 6);
+
+        let not_found = |name: &str| {
+            UncheckedPkg(
+                PkgNameAndVersion(name.to_owned(), "1.0.0".to_owned()),
+                Arc::new(DebsError::PackageNotFound(name.to_owned())),
+            )
+        };
+        let not_in_cache = UncheckedPkg(
+            PkgNameAndVersion("c".to_owned(), "1.0.0".to_owned()),
+            Arc::new(DebsError::NotInCache("c".to_owned())),
+        );
+
+        let same = DebsError::UncheckedPackages(vec![not_found("a"), not_found("b")]);
+        assert_eq!(same.exit_code(), 7);
+        assert_eq!(same.to_string(), "2 package(s) could not be checked");
+        assert_eq!(
+            DebsError::UncheckedPackages(vec![not_found("a"), not_in_cache]).exit_code(),
+            1
+        );
+        assert_eq!(DebsError::StaleLockfile(1).exit_code(), 9);
+    }
+}
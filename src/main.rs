@@ -1,16 +1,32 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use clap::{arg, Command};
+use clap::builder::RangedU64ValueParser;
+use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
 
+mod check;
 mod deprecated;
+mod error;
 mod old;
 mod package_json;
 mod registry;
+mod report;
+mod semver;
 mod types;
+mod verify_lock;
+mod why;
+mod workspaces;
 
 use deprecated::get_deprecated_packages;
+use error::DebsError;
 use old::get_old_packages;
-use package_json::parse_package_json;
+use package_json::{parse_lockfile, parse_package_json};
+use registry::{Cache, Registry, DEFAULT_CONCURRENCY, DEFAULT_TIMEOUT_SECS};
+use report::Format;
+use types::DepCategory;
+use verify_lock::get_verify_lock_output;
+use why::get_why_output;
+use workspaces::discover_workspaces;
 
 /// Initializes a command line interface using the [`clap`] module.
 ///
@@ -19,6 +35,15 @@ fn cli() -> Command {
     Command::new("debs")
         .subcommand_required(true)
         .arg_required_else_help(true)
+        // `global` makes the option available to (and readable from) every subcommand
+        .arg(arg!(--registry <URL> "Registry to fetch package data from (default: $DEBS_REGISTRY, then .npmrc, then registry.npmjs.org)").global(true))
+        .arg(arg!(--"fail-on-errors" "Exit with an error code when some packages could not be checked").global(true))
+        .arg(arg!(--concurrency <N> "Maximum number of requests sent to the registry at the same time").value_parser(RangedU64ValueParser::<usize>::new().range(1..)).default_value(DEFAULT_CONCURRENCY.to_string()).global(true))
+        .arg(arg!(--timeout <SECONDS> "Seconds after which a request to the registry is abandoned and retried").value_parser(value_parser!(u64).range(1..)).default_value(DEFAULT_TIMEOUT_SECS.to_string()).global(true))
+        .arg(arg!(--"no-cache" "Neither read nor write cached registry responses").global(true))
+        .arg(arg!(--refresh "Revalidate every cached registry response, regardless of its age").global(true))
+        .arg(arg!(--offline "Only use cached registry responses, never contacting the registry").conflicts_with("no-cache").global(true))
+        .arg(arg!(--"cache-ttl" <HOURS> "Hours during which cached registry responses are used as-is").value_parser(value_parser!(u64)).default_value("24").global(true))
         .subcommand(
             Command::new("old")
                 .about("Filter packages older than [YEARS (default: 4)]")
@@ -28,33 +53,164 @@ fn cli() -> Command {
                 .arg(arg!(-s --since <YEARS> "Minimum age of packages to be displayed").default_value("4"))
                 // no parameter is specified (e.g. no "<PROD>") so this is a boolean
                 .arg(arg!(-p --production "Add this option to exclusively show packages used in production").default_value("false"))
+                .arg(omit_arg())
+                .arg(include_arg())
+                .arg(arg!(--transitive "Also check the dependencies of dependencies, listed in the lockfile"))
+                .arg(format_arg())
                 .arg(arg!(--path <PATH> "Specify the path to, but not including, the root package.json").default_value(""))
             )
             .subcommand(
                 Command::new("deprecated")
                 .about("Filter deprecated packages")
                 .arg(arg!(-p --production "Add this option to exclusively show packages used in production").default_value("false"))
+                .arg(omit_arg())
+                .arg(include_arg())
+                .arg(arg!(--transitive "Also check the dependencies of dependencies, listed in the lockfile"))
+                .arg(format_arg())
                 .arg(arg!(--path <PATH> "Specify the path to the root package.json").default_value(""))
             )
+            .subcommand(
+                Command::new("why")
+                .about("Show the chains of dependencies leading to an installed package")
+                .arg(arg!(<PACKAGE> "Package to look for, as <name> or <name>@<version>"))
+                .arg(arg!(-p --production "Add this option to only look through packages used in production").default_value("false"))
+                .arg(omit_arg())
+                .arg(include_arg())
+                .arg(arg!(--path <PATH> "Specify the path to the root package.json").default_value(""))
+            )
+            .subcommand(
+                Command::new("verify-lock")
+                .about("Check that installed versions satisfy the ranges declared in package.json")
+                .arg(arg!(-p --production "Add this option to exclusively check packages used in production").default_value("false"))
+                .arg(omit_arg())
+                .arg(include_arg())
+                .arg(arg!(--path <PATH> "Specify the path to the root package.json").default_value(""))
+            )
+}
+
+/// Categories of dependencies accepted by `--omit` and `--include`, like npm's options of the same name.
+static CATEGORY_VALUES: [&str; 4] = ["dev", "optional", "peer", "bundled"];
+
+fn omit_arg() -> clap::Arg {
+    arg!(--omit <CATEGORY> "Category of dependencies not to check, can be repeated")
+        .value_parser(CATEGORY_VALUES)
+        .action(ArgAction::Append)
+}
+
+fn include_arg() -> clap::Arg {
+    arg!(--include <CATEGORY> "Category of dependencies to check even when omitted, can be repeated")
+        .value_parser(CATEGORY_VALUES)
+        .action(ArgAction::Append)
+}
+
+/// Format of the output of `old` and `deprecated`, the only commands accepting `--format`.
+fn format_arg() -> clap::Arg {
+    arg!(--format <FORMAT> "Format of the output")
+        .value_parser(value_parser!(Format))
+        .default_value("text")
+}
+
+/// Categories of dependencies to check: all of them, except those given to `--omit`
+/// (and development dependencies with `--production`), unless also given to `--include`.
+fn dep_categories(sub_matches: &ArgMatches) -> Vec<DepCategory> {
+    let values = |id: &str| -> Vec<&str> {
+        sub_matches
+            .get_many::<String>(id)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect()
+    };
+
+    let mut omitted = values("omit");
+
+    if *sub_matches
+        .get_one::<bool>("production")
+        .expect("defaulted in clap")
+    {
+        omitted.push(DepCategory::Development.as_str());
+    }
+
+    let included = values("include");
+
+    DepCategory::ALL
+        .into_iter()
+        .filter(|category| {
+            !omitted.contains(&category.as_str()) || included.contains(&category.as_str())
+        })
+        .collect()
+}
+
+/// Registry to fetch package data from, configured by the global options.
+fn registry(sub_matches: &ArgMatches, path: &Path) -> Registry {
+    let no_cache = sub_matches
+        .get_one::<bool>("no-cache")
+        .expect("defaulted in clap");
+
+    let cache = if *no_cache {
+        None
+    } else {
+        let refresh = sub_matches
+            .get_one::<bool>("refresh")
+            .expect("defaulted in clap");
+
+        let ttl = sub_matches
+            .get_one::<u64>("cache-ttl")
+            .expect("defaulted in clap");
+
+        Cache::default_dir().map(|dir| Cache::new(dir, *ttl, *refresh))
+    };
+
+    Registry::resolve(
+        sub_matches
+            .get_one::<String>("registry")
+            .map(String::as_str),
+        path,
+    )
+    .with_cache(cache)
+    .with_offline(
+        *sub_matches
+            .get_one::<bool>("offline")
+            .expect("defaulted in clap"),
+    )
+    .with_concurrency(
+        *sub_matches
+            .get_one::<usize>("concurrency")
+            .expect("defaulted in clap"),
+    )
+    .with_timeout(
+        *sub_matches
+            .get_one::<u64>("timeout")
+            .expect("defaulted in clap"),
+    )
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+/// Runs the command given on the command line.
+///
+/// Invalid arguments are reported by clap, which exits on its own.
+async fn run() -> Result<(), DebsError> {
     let matches = cli().get_matches();
 
     match matches.subcommand() {
         Some((command_name, sub_matches)) => {
-            let production_pkgs_only = sub_matches
-                .get_one::<bool>("production")
-                .expect("defaulted in clap");
-
             let path = sub_matches
                 .get_one::<String>("path")
                 .expect("defaulted in clap");
 
             let mut path = PathBuf::from(path);
 
-            if let false = path.is_absolute() {
+            if !path.is_absolute() {
                 // use relative path if fails to read cwd
                 // then again, if reading the cwd fails, you probably have other things to worry about
                 path = std::env::current_dir().unwrap_or_default().join(path);
@@ -63,31 +219,119 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // root package.json
             let pkg_json = parse_package_json(&path)?;
 
-            match command_name {
+            // the root package is the only workspace of projects without `workspaces`
+            let (workspaces, missing_workspaces) = discover_workspaces(&path, &pkg_json)?;
+
+            for workspace in missing_workspaces {
+                eprintln!("warning: workspace \"{workspace}\" listed in package.json matches no folder with a package.json, skipping");
+            }
+
+            // read once, and shared by every workspace
+            let lock = parse_lockfile(&path, &workspaces)?;
+
+            let fail_on_errors = sub_matches
+                .get_one::<bool>("fail-on-errors")
+                .expect("defaulted in clap");
+
+            let unchecked = match command_name {
                 "deprecated" => {
-                    match pkg_json.workspaces {
-                        None => panic!("No workspaces found in package.json. Cannot read the path to the necessary dependency information."),
-                        // `*` operator is used to deref a reference, here: &bool -> bool (same use as in C/C++)
-                        // `&` creates an immutable reference to `workspaces`. There can be any amount of immutable refs at a single point in time.
-                        // `&mut` would create a mutable reference. There can only be a single mut ref to something at a single point in time.
-                        Some(workspaces) => println!("{}", get_deprecated_packages(&path, &workspaces, !(*production_pkgs_only)).await),
+                    let format = *sub_matches
+                        .get_one::<Format>("format")
+                        .expect("defaulted in clap");
+
+                    let transitive = sub_matches
+                        .get_one::<bool>("transitive")
+                        .expect("defaulted in clap");
+
+                    // `*` operator is used to deref a reference, here: &bool -> bool (same use as in C/C++)
+                    // `&` creates an immutable reference to `workspaces`. There can be any amount of immutable refs at a single point in time.
+                    // `&mut` would create a mutable reference. There can only be a single mut ref to something at a single point in time.
+                    let (output, unchecked) = get_deprecated_packages(
+                        &registry(sub_matches, &path),
+                        &path,
+                        &workspaces,
+                        &lock,
+                        &dep_categories(sub_matches),
+                        *transitive,
+                        format,
+                    )
+                    .await?;
+
+                    match format {
+                        Format::Text => println!("{output}"),
+                        // other formats end with a newline on their own
+                        _ => print!("{output}"),
                     }
+
+                    unchecked
                 }
                 "old" => {
+                    let format = *sub_matches
+                        .get_one::<Format>("format")
+                        .expect("defaulted in clap");
+
                     let since = sub_matches
                         .get_one::<String>("since")
                         .expect("defaulted in clap");
 
                     let since = since.parse::<u32>().unwrap_or(4);
 
-                    match pkg_json.workspaces {
-                        None => panic!("No workspaces found in package.json. Cannot read the path to the necessary dependency information."),
-                        // passing stdout as `writer` to `old`, to showcase a different way to handle testing output than returning a String
-                        // inside `old`'s test, we pass a Vec<u8> instead of stdout to collect the output
-                        Some(workspaces) => {get_old_packages(since, &path, &workspaces, &mut std::io::stdout(), !(*production_pkgs_only)).await?;},
+                    let transitive = sub_matches
+                        .get_one::<bool>("transitive")
+                        .expect("defaulted in clap");
+
+                    // passing stdout as `writer` to `old`, to showcase a different way to handle testing output than returning a String
+                    // inside `old`'s test, we pass a Vec<u8> instead of stdout to collect the output
+                    get_old_packages(
+                        &registry(sub_matches, &path),
+                        since,
+                        &path,
+                        &workspaces,
+                        &lock,
+                        &mut std::io::stdout(),
+                        format,
+                        &dep_categories(sub_matches),
+                        *transitive,
+                    )
+                    .await?
+                }
+                "why" => {
+                    let package = sub_matches
+                        .get_one::<String>("PACKAGE")
+                        .expect("required in clap");
+
+                    let output = get_why_output(
+                        &path,
+                        &workspaces,
+                        &lock,
+                        package,
+                        &dep_categories(sub_matches),
+                    )?;
+                    println!("{output}");
+                    // the registry is not queried
+                    vec![]
+                }
+                "verify-lock" => {
+                    let (output, num_stale) = get_verify_lock_output(
+                        &path,
+                        &workspaces,
+                        &lock,
+                        &dep_categories(sub_matches),
+                    )?;
+                    println!("{output}");
+
+                    if num_stale > 0 {
+                        return Err(DebsError::StaleLockfile(num_stale));
                     }
+
+                    // the registry is not queried
+                    vec![]
                 }
                 _ => unreachable!(),
+            };
+
+            if *fail_on_errors && !unchecked.is_empty() {
+                return Err(DebsError::UncheckedPackages(unchecked));
             }
         }
         _ => unreachable!(),
@@ -100,17 +344,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 mod tests {
     // LINK CLIをテストする：　https://rust-cli.github.io/book/tutorial/testing.html
     // LINK rustlingsのcliテスト： https://github.com/rust-lang/rustlings/blob/main/tests/integration_tests.rs
-    //! ``` rust
-    //! #[test]
-    //! fn run_single_compile_success() {
-    //!     Command::cargo_bin("rustlings")
-    //!         .unwrap()
-    //!         .args(&["run", "compSuccess"])
-    //!         .current_dir("tests/fixture/success/") // --path を使わなくていい
-    //!         .assert()
-    //!         .success();
-    //! }
-    //! ```
+    // ``` rust
+    // #[test]
+    // fn run_single_compile_success() {
+    //     Command::cargo_bin("rustlings")
+    //         .unwrap()
+    //         .args(&["run", "compSuccess"])
+    //         .current_dir("tests/fixture/success/") // --path を使わなくていい
+    //         .assert()
+    //         .success();
+    // }
+    // ```
 
     use std::collections::HashMap;
     use std::error::Error;
@@ -25,16 +25,26 @@
 //! [npm registry]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md
 //! [RFC 3339]: https://www.rfc-editor.org/rfc/rfc3339#page-6
 
-use std::error::Error;
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 
 use chrono::{DateTime, FixedOffset, Utc};
 use futures::future;
 use once_cell::sync::Lazy;
-
-use crate::package_json::get_deps_version;
-use crate::registry::PackageMetadata;
-use crate::types::{PkgName, PkgNameAndVersion, Version};
+use serde::Serialize;
+
+use crate::check::{filter_transitive, get_workspace_deps, WorkspaceDeps};
+use crate::error::DebsError;
+use crate::package_json::Lockfile;
+use crate::registry::{PackageMetadata, Registry};
+use crate::report::text::{
+    get_non_registry_output, get_total, get_unchecked_output, get_unmet_peers_output,
+};
+use crate::report::{write_report, Check, Field, Finding, Format, WorkspaceResults};
+use crate::types::{
+    DepCategory, DepsByCategory, PkgName, PkgNameAndVersion, UncheckedPkg, Version,
+};
 
 use super::registry::pkg_info;
 
@@ -57,7 +67,8 @@ pub struct PkgAgeDetails(
 /// Format containing all the necessary version and age info later shown to the user.
 ///
 /// This includes info about the latest version of the package, something not present in [`PkgAgeDetails`].
-#[derive(Clone, Debug, PartialEq, Eq)]
+/// Also serialized as is in the JSON output, see [`crate::report`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub struct OldPkgDetails {
     pub name: PkgName,
     pub local_version: Version,
@@ -68,168 +79,412 @@ pub struct OldPkgDetails {
     pub age_latest_version: u32,
 }
 
-/// Takes workspace paths and returns a string describing packages older than the provided age limit.
+impl Finding for OldPkgDetails {
+    const COLUMNS: &'static [&'static str] = &["Published", "Age", "Latest", "Gap"];
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn local_version(&self) -> &str {
+        &self.local_version
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "{}@{} is {} years old ({}), {} older than latest @{} ({})",
+            self.name,
+            self.local_version,
+            self.age_local_version,
+            self.publication_local_version.format("%d/%m/%Y"),
+            self.age_local_version
+                .saturating_sub(self.age_latest_version),
+            self.latest_version,
+            self.publication_latest_version.format("%d/%m/%Y"),
+        )
+    }
+
+    fn cells(&self) -> Vec<String> {
+        let years = |num_years: u32| match num_years {
+            1 => "1 year".to_owned(),
+            _ => format!("{num_years} years"),
+        };
+
+        vec![
+            self.publication_local_version
+                .format("%Y-%m-%d")
+                .to_string(),
+            years(self.age_local_version),
+            format!(
+                "{} ({})",
+                self.latest_version,
+                self.publication_latest_version.format("%Y-%m-%d")
+            ),
+            years(
+                self.age_local_version
+                    .saturating_sub(self.age_latest_version),
+            ),
+        ]
+    }
+
+    const FIELDS: &'static [Field<Self>] = &[
+        ("publication_local_version", |pkg| {
+            pkg.publication_local_version.format("%Y-%m-%d").to_string()
+        }),
+        ("age_local_version", |pkg| pkg.age_local_version.to_string()),
+        ("latest_version", |pkg| pkg.latest_version.clone()),
+        ("publication_latest_version", |pkg| {
+            pkg.publication_latest_version
+                .format("%Y-%m-%d")
+                .to_string()
+        }),
+        ("age_latest_version", |pkg| {
+            pkg.age_latest_version.to_string()
+        }),
+    ];
+}
+
+/// Old transitive package, along with the direct dependencies pulling it in.
+type TransitiveOldPkg = (OldPkgDetails, Vec<PkgName>);
+
+/// Result of the age check of each evaluated package: `Some` details for old packages,
+/// `None` for recent ones, or the error met while checking the package.
+type Evaluations = HashMap<PkgNameAndVersion, Result<Option<OldPkgDetails>, Arc<DebsError>>>;
+
+/// Takes workspace paths and writes a description of packages older than the provided age limit,
+/// in the given format.
+///
+/// Returns the packages that could not be checked, which are listed along the old ones.
 ///
 /// ## Parameters
 ///
 /// | Parameter | Description |
 /// | --------- | ----------- |
+/// | **registry:**       | Registry to fetch package metadata from. |
 /// | **since:**          | Arbitrary amount of **years** since when a package version must have been published to be considered old. |
-/// | **prod_pkgs_only:** | Ignore development dependencies. |
+/// | **categories:**     | Categories of direct dependencies to check. Unmet peer dependencies are listed when peer dependencies are checked. |
+/// | **transitive:**     | Also check the dependencies of dependencies, see [`crate::package_json::get_transitive_deps`]. |
 /// | **path:**           | Path to the root package.json containing workspace names. |
 /// | **workspaces:**     | Workspaces to check installed dependencies and versions from. |
+/// | **lock:**           | Root lockfile, listing the installed versions. |
+/// | **format:**         | Format of the output. |
+#[allow(clippy::too_many_arguments)]
 pub async fn get_old_packages(
+    registry: &Registry,
     since: u32,
     path: &Path,
     workspaces: &[String],
+    lock: &Lockfile,
     mut writer: impl std::io::Write,
-    include_development_packages: bool,
-) -> Result<(), std::io::Error> {
-    let deps_by_workspace: Vec<(Vec<PkgNameAndVersion>, Vec<PkgNameAndVersion>)> = workspaces
+    format: Format,
+    categories: &[DepCategory],
+    include_transitive_packages: bool,
+) -> Result<Vec<UncheckedPkg>, DebsError> {
+    let deps_by_workspace = workspaces
         .iter()
-        .flat_map(|workspace| {
-            get_deps_version(
-                &path.join(workspace),
+        .map(|workspace| {
+            get_workspace_deps(
                 path,
-                // names for the deps in the `frontend/` start with an extra prefix
-                workspace == "frontend/",
+                workspace,
+                lock,
+                categories,
+                include_transitive_packages,
             )
         })
-        .collect();
+        .collect::<Result<Vec<_>, DebsError>>()?;
 
-    // Wait for all deps to be tested for age, and zip them together with their workspace name
-    let old_deps_by_workspace = future::join_all(
+    // Evaluate every package used in any workspace once
+    let evaluations = evaluate_packages(
+        registry,
+        since,
         deps_by_workspace
-            .into_iter()
-            .map(|(prod, dev)| filter_old_packages(since, (prod, dev))),
+            .iter()
+            .flat_map(WorkspaceDeps::registry_pkgs),
     )
-    .await
-    .into_iter()
-    // Since errors were already "flattened" inside of `get_old_deps` we can safely unwrap here
-    .map(|details| details.unwrap())
-    .zip(workspaces);
-
-    for ((prod, dev), workspace) in old_deps_by_workspace {
-        writeln!(writer, "\n[{workspace}] old packages:")?;
-        get_output((&prod, &dev), &mut writer, include_development_packages)?;
-    }
+    .await;
 
-    Ok(())
+    let results: Vec<WorkspaceResults<OldPkgDetails>> = deps_by_workspace
+        .into_iter()
+        .zip(workspaces)
+        .map(
+            |(
+                WorkspaceDeps {
+                    direct,
+                    transitive,
+                    unmet_peers,
+                    non_registry,
+                },
+                workspace,
+            )| {
+                let (old, mut unchecked) = filter_old_packages(&evaluations, &direct);
+
+                let transitive_old = transitive.as_ref().map(|transitive| {
+                    let (transitive_old, transitive_unchecked) =
+                        filter_transitive(transitive, |pkg| evaluation(&evaluations, pkg));
+
+                    unchecked.extend(transitive_unchecked);
+
+                    transitive_old
+                });
+
+                WorkspaceResults {
+                    workspace: workspace.clone(),
+                    direct: old,
+                    transitive: transitive_old,
+                    unmet_peers,
+                    non_registry,
+                    unchecked,
+                    checked: direct,
+                    checked_transitive: transitive,
+                }
+            },
+        )
+        .collect();
+
+    write_report(format, Check::Old, path, &results, &mut writer, |writer| {
+        for results in &results {
+            writeln!(writer, "\n[{}] old packages:", results.workspace)?;
+            get_output(&results.direct, results.transitive.as_deref(), &mut *writer)?;
+            write!(writer, "{}", get_unmet_peers_output(&results.unmet_peers))?;
+            write!(writer, "{}", get_non_registry_output(&results.non_registry))?;
+            write!(writer, "{}", get_unchecked_output(&results.unchecked))?;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(results
+        .into_iter()
+        .flat_map(|results| results.unchecked)
+        .collect())
 }
 
-/// Get dates for the package versions used in the project, and filter old ones.
-async fn filter_old_packages(
+/// Get dates for each distinct package version among `deps`, concurrently, and check whether it is old.
+async fn evaluate_packages<'a>(
+    registry: &Registry,
     since: u32,
-    (prod_deps, dev_deps): (Vec<PkgNameAndVersion>, Vec<PkgNameAndVersion>),
-) -> Result<(Vec<OldPkgDetails>, Vec<OldPkgDetails>), Box<dyn Error>> {
-    async fn worker(
-        since: u32,
-        deps: Vec<PkgNameAndVersion>,
-    ) -> Result<Vec<OldPkgDetails>, Box<dyn Error>> {
-        let maybe_pkgs_data = future::join_all(deps.into_iter().map(to_pkg_date_tuple)).await;
-
-        // vec_of_res.into_iter().flatten() filters Ok values
-        // Remove errors (either missing package info, or wrong publication date formatting)
-        // and filter old packages.
-        let pkgs = maybe_pkgs_data
-            .into_iter()
-            .flatten()
-            .filter(|PkgAgeDetails(_, _, _, age_version, _)| *age_version > since)
-            .collect();
-
-        Ok(add_latest_version_info(pkgs))
+    deps: impl Iterator<Item = &'a PkgNameAndVersion>,
+) -> Evaluations {
+    let mut pkgs: Vec<PkgNameAndVersion> = deps.cloned().collect();
+
+    pkgs.sort_unstable();
+    pkgs.dedup();
+
+    let maybe_pkgs_data = future::join_all(
+        pkgs.iter()
+            .cloned()
+            .map(|pkg| to_pkg_date_tuple(registry, pkg)),
+    )
+    .await;
+
+    let mut evaluations = Evaluations::new();
+    let mut old_pkgs = Vec::new();
+
+    // Set errors (either missing package info, or wrong publication date formatting) aside,
+    // to let the user know these packages could not be checked, and filter old packages.
+    for (maybe_pkg_data, pkg) in maybe_pkgs_data.into_iter().zip(pkgs) {
+        match maybe_pkg_data {
+            Ok(pkg_data) if pkg_data.3 > since => old_pkgs.push(pkg_data),
+            Ok(_) => {
+                evaluations.insert(pkg, Ok(None));
+            }
+            Err(e) => {
+                evaluations.insert(pkg, Err(Arc::new(e)));
+            }
+        }
     }
 
-    Ok((
-        worker(since, prod_deps).await?,
-        worker(since, dev_deps).await?,
-    ))
+    let (old_pkgs, unchecked) = add_latest_version_info(old_pkgs);
+
+    for details in old_pkgs {
+        let pkg = PkgNameAndVersion(details.name.clone(), details.local_version.clone());
+
+        evaluations.insert(pkg, Ok(Some(details)));
+    }
+
+    for UncheckedPkg(pkg, e) in unchecked {
+        evaluations.insert(pkg, Err(e));
+    }
+
+    evaluations
+}
+
+/// Return old packages of each category, read from the previously computed `evaluations`,
+/// followed by the packages that could not be checked (e.g. because of network errors or unknown versions).
+///
+/// Every category of `deps` is returned, even without old packages.
+fn filter_old_packages(
+    evaluations: &Evaluations,
+    deps: &DepsByCategory<PkgNameAndVersion>,
+) -> (DepsByCategory<OldPkgDetails>, Vec<UncheckedPkg>) {
+    let mut unchecked = Vec::new();
+
+    let old = deps
+        .iter()
+        .map(|(category, deps)| {
+            let (old, category_unchecked) = filter_old(evaluations, deps);
+
+            unchecked.extend(category_unchecked);
+
+            (*category, old)
+        })
+        .collect();
+
+    (old, unchecked)
+}
+
+/// Filters old packages among `deps`, and packages that could not be checked, in the order of `deps`.
+fn filter_old(
+    evaluations: &Evaluations,
+    deps: &[PkgNameAndVersion],
+) -> (Vec<OldPkgDetails>, Vec<UncheckedPkg>) {
+    let mut unchecked = Vec::new();
+
+    let old = deps
+        .iter()
+        .filter_map(|pkg| {
+            evaluation(evaluations, pkg)
+                .map_err(|e| unchecked.push(UncheckedPkg(pkg.clone(), e)))
+                .ok()
+                .flatten()
+        })
+        .collect();
+
+    (old, unchecked)
+}
+
+/// Details of `pkg` when it is old, read from the previously computed `evaluations`.
+fn evaluation(
+    evaluations: &Evaluations,
+    pkg: &PkgNameAndVersion,
+) -> Result<Option<OldPkgDetails>, Arc<DebsError>> {
+    evaluations
+        .get(pkg)
+        .expect("every package is evaluated")
+        .clone()
 }
 
 /// Augment a package with the date at which its in-use version was published and its age.
 async fn to_pkg_date_tuple(
+    registry: &Registry,
     PkgNameAndVersion(pkg, version): PkgNameAndVersion,
-) -> Result<PkgAgeDetails, Box<dyn Error>> {
-    let pkg_meta = pkg_info(pkg.as_str()).await?;
+) -> Result<PkgAgeDetails, DebsError> {
+    let pkg_meta = pkg_info(registry, pkg.as_str()).await?;
 
     match pkg_meta.time.get(version.as_str()) {
         Some(version_date) => {
-            let version_update = DateTime::parse_from_rfc3339(version_date.as_str()).unwrap();
-
-            let age = NOW.years_since(version_update.into()).unwrap();
+            let (version_update, age) = parse_publication_date(&pkg, version_date)?;
 
             Ok(PkgAgeDetails(pkg, version, version_update, age, pkg_meta))
         }
-        None => Err(Box::<dyn Error>::from(format!(
-            "Version {version} of {pkg} not found in timetable"
-        ))),
+        None => Err(DebsError::VersionNotFound(PkgNameAndVersion(pkg, version))),
     }
 }
 
+/// Parses a publication date from the registry, and computes its age in **years**.
+///
+/// Dates that are not in the RFC 3339 format, or that are in the future, are invalid.
+fn parse_publication_date(
+    pkg: &str,
+    date: &str,
+) -> Result<(DateTime<FixedOffset>, u32), DebsError> {
+    let invalid_date = || DebsError::InvalidDate {
+        pkg: pkg.to_owned(),
+        date: date.to_owned(),
+    };
+
+    let publication = DateTime::parse_from_rfc3339(date).map_err(|_| invalid_date())?;
+
+    // `None` when the date is after "now"
+    let age = NOW
+        .years_since(publication.into())
+        .ok_or_else(invalid_date)?;
+
+    Ok((publication, age))
+}
+
 /// Adds the latest version, its date of publication and age to the package data.
 ///
 /// This info is later output to the user to allow age comparison of the installed version
 /// with the latest version.
-fn add_latest_version_info(pkgs: Vec<PkgAgeDetails>) -> Vec<OldPkgDetails> {
-    pkgs.into_iter()
-        .map(
-            |PkgAgeDetails(
-                name,
-                local_version,
-                publication_local_version,
-                age_local_version,
-                pkg_metadata,
-            )| {
-                let last_update_str = pkg_metadata.time.get("modified").unwrap();
+///
+/// Packages whose metadata lacks this info (e.g. unpublished packages) are returned separately,
+/// with the reason why they could not be checked.
+fn add_latest_version_info(pkgs: Vec<PkgAgeDetails>) -> (Vec<OldPkgDetails>, Vec<UncheckedPkg>) {
+    let mut unchecked = Vec::new();
+
+    let old_pkgs = pkgs
+        .into_iter()
+        .filter_map(|pkg| {
+            let pkg_name_and_version = PkgNameAndVersion(pkg.0.clone(), pkg.1.clone());
+
+            to_old_pkg_details(pkg)
+                .map_err(|e| unchecked.push(UncheckedPkg(pkg_name_and_version, Arc::new(e))))
+                .ok()
+        })
+        .collect();
 
-                let date_latest_version =
-                    DateTime::parse_from_rfc3339(last_update_str.as_str()).unwrap();
+    (old_pkgs, unchecked)
+}
 
-                let age_latest_version = NOW.years_since(date_latest_version.into()).unwrap();
+fn to_old_pkg_details(
+    PkgAgeDetails(name, local_version, publication_local_version, age_local_version, pkg_metadata): PkgAgeDetails,
+) -> Result<OldPkgDetails, DebsError> {
+    let missing_field = |field: &str| DebsError::MissingMetadata {
+        pkg: name.clone(),
+        field: field.to_owned(),
+    };
 
-                OldPkgDetails {
-                    name,
-                    local_version,
-                    publication_local_version,
-                    age_local_version,
-                    latest_version: pkg_metadata.dist_tags.get("latest").unwrap().to_string(),
-                    publication_latest_version: date_latest_version,
-                    age_latest_version,
-                }
-            },
-        )
-        .collect()
+    let last_update_str = pkg_metadata
+        .time
+        .get("modified")
+        .ok_or_else(|| missing_field("time.modified"))?;
+
+    let (date_latest_version, age_latest_version) = parse_publication_date(&name, last_update_str)?;
+
+    let latest_version = pkg_metadata
+        .dist_tags
+        .get("latest")
+        .ok_or_else(|| missing_field("dist-tags.latest"))?
+        .to_string();
+
+    Ok(OldPkgDetails {
+        name,
+        local_version,
+        publication_local_version,
+        age_local_version,
+        latest_version,
+        publication_latest_version: date_latest_version,
+        age_latest_version,
+    })
 }
 
 /// Returns the full output for the `old` task, including workspace headers and statistics.
+///
+/// Each category of `old` is shown (and counted), even without old packages.
+/// Transitive packages are only shown (and counted) in `--transitive` mode, when given.
 fn get_output(
-    (prod_old, dev_old): (&[OldPkgDetails], &[OldPkgDetails]),
+    old: &DepsByCategory<OldPkgDetails>,
+    transitive_old: Option<&[TransitiveOldPkg]>,
     mut writer: impl std::io::Write,
-    include_development_packages: bool,
 ) -> Result<(), std::io::Error> {
-    if include_development_packages {
-        get_pkgs_output(prod_old, Some("production:"), &mut writer)?;
-        get_pkgs_output(dev_old, Some("development:"), &mut writer)?;
-
-        let num_old_prods = prod_old.len();
-        let num_old_devs = dev_old.len();
-
-        writeln!(writer, "\n  total: {num_old_prods} old dependenc{end_1}, {num_old_devs} old dev dependenc{end_2}",
-            end_1 = if num_old_prods == 1 { "y" } else { "ies" },
-            end_2 = if num_old_devs == 1 { "y" } else { "ies" }
-        )?;
-    } else {
-        get_pkgs_output(prod_old, None, &mut writer)?;
-
-        let num_old_pkgs = prod_old.len();
-
-        writeln!(
-            writer,
-            "\n  total: {num_old_pkgs} old production dependencies",
-        )?;
+    // production packages need a header to be told apart from other categories, and from transitive ones
+    let with_headers = transitive_old.is_some()
+        || old
+            .keys()
+            .any(|category| *category != DepCategory::Production);
+
+    for (category, pkgs) in old {
+        get_pkgs_output(pkgs, with_headers.then(|| category.header()), &mut writer)?;
     }
 
-    Ok(())
+    get_transitive_pkgs_output(transitive_old, &mut writer)?;
+
+    writeln!(
+        writer,
+        "\n  total: {}",
+        get_total(old, transitive_old.map(<[_]>::len), "old")
+    )
 }
 
 /// Return the output for old packages, without headers/footers.
@@ -255,7 +510,41 @@ fn get_pkgs_output(
         None => " ".repeat(2),
     };
 
-    for OldPkgDetails {
+    for pkg in pkgs {
+        get_pkg_output(pkg, &extra_space, &mut writer)?;
+    }
+
+    Ok(())
+}
+
+/// Return the output for old transitive packages, under the "transitive:" header,
+/// mentioning the direct dependencies pulling each of them in.
+fn get_transitive_pkgs_output(
+    pkgs: Option<&[TransitiveOldPkg]>,
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let pkgs = match pkgs {
+        Some(pkgs) if !pkgs.is_empty() => pkgs,
+        _ => return Ok(()),
+    };
+
+    writeln!(writer, "\n  transitive:")?;
+
+    for (pkg, required_by) in pkgs {
+        get_pkg_output(pkg, "    ", &mut writer)?;
+
+        writeln!(
+            writer,
+            "            -> required by {}",
+            required_by.join(", ")
+        )?;
+    }
+
+    Ok(())
+}
+
+fn get_pkg_output(
+    OldPkgDetails {
         name,
         local_version: version,
         publication_local_version: date_version,
@@ -264,39 +553,43 @@ fn get_pkgs_output(
         publication_latest_version: date_latest_version,
         age_latest_version,
         ..
-    } in pkgs
-    {
-        let simple_date_version = date_version.format("%d/%m/%Y");
+    }: &OldPkgDetails,
+    extra_space: &str,
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let simple_date_version = date_version.format("%d/%m/%Y");
 
-        let simple_date_latest_version = date_latest_version.format("%d/%m/%Y");
+    let simple_date_latest_version = date_latest_version.format("%d/%m/%Y");
 
-        let age_diff = age_version - age_latest_version;
+    // the latest version can be older than the installed one, e.g. when `latest` points to an older release line
+    let age_diff = age_version.saturating_sub(*age_latest_version);
 
-        writeln!(
-            writer,
-            "\n{extra_space}{name}@{version} ({simple_date_version})",
-        )?;
+    writeln!(
+        writer,
+        "\n{extra_space}{name}@{version} ({simple_date_version})",
+    )?;
 
-        writeln!(
-            writer,
-            "{extra_space}    -> {age_version} years old, {age_diff} older than latest",
-        )?;
-
-        writeln!(
-            writer,
-            "{extra_space}        -> latest @{latest_version} ({simple_date_latest_version})",
-        )?;
-    }
+    writeln!(
+        writer,
+        "{extra_space}    -> {age_version} years old, {age_diff} older than latest",
+    )?;
 
-    Ok(())
+    writeln!(
+        writer,
+        "{extra_space}        -> latest @{latest_version} ({simple_date_latest_version})",
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::error::Error;
 
     use chrono::TimeZone;
 
+    use crate::package_json::parse_lockfile;
+    use crate::types::TransitivePkg;
+
     // imports everything from `old` module
     use super::*;
 
@@ -378,7 +671,18 @@ mod tests {
 
         let mut chars = Vec::new();
 
-        get_old_packages(4, path, &workspaces, &mut chars, false).await?;
+        get_old_packages(
+            &Registry::default(),
+            4,
+            path,
+            &workspaces,
+            &parse_lockfile(path, &workspaces)?,
+            &mut chars,
+            Format::Text,
+            &[DepCategory::Production],
+            false,
+        )
+        .await?;
 
         let output = String::from_utf8(chars)?;
 
@@ -395,11 +699,21 @@ mod tests {
     async fn should_get_old_deps() -> Result<(), Box<dyn Error>> {
         let pkg1 = PkgNameAndVersion("chartjs-plugin-datalabels".to_owned(), "0.3.0".to_owned());
         let pkg2 = PkgNameAndVersion("file-loader".to_owned(), "1.1.11".to_owned());
-        let maybe_old = filter_old_packages(4, (vec![pkg1], vec![pkg2])).await;
-
-        assert!(maybe_old.is_ok());
+        let evaluations =
+            evaluate_packages(&Registry::default(), 4, [&pkg1, &pkg2].into_iter()).await;
+
+        let (old, _) = filter_old_packages(
+            &evaluations,
+            &DepsByCategory::from([
+                (DepCategory::Production, vec![pkg1]),
+                (DepCategory::Development, vec![pkg2]),
+            ]),
+        );
 
-        let (old_deps, old_dev_deps) = maybe_old?;
+        let (old_deps, old_dev_deps) = (
+            &old[&DepCategory::Production],
+            &old[&DepCategory::Development],
+        );
 
         assert!(old_deps.iter().any(
             |OldPkgDetails {
@@ -424,16 +738,21 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn should_filter_wrong_pkg_info() -> Result<(), Box<dyn Error>> {
         let pkg1 = PkgNameAndVersion("wrong-info".to_owned(), "A.3.0".to_owned());
-        let maybe_old = filter_old_packages(4, (vec![pkg1], vec![])).await;
-
-        assert!(maybe_old.is_ok());
+        let evaluations = evaluate_packages(&Registry::default(), 4, [&pkg1].into_iter()).await;
 
-        let (prod_deps, _) = maybe_old?;
+        let (old, unchecked) = filter_old_packages(
+            &evaluations,
+            &DepsByCategory::from([(DepCategory::Production, vec![pkg1])]),
+        );
 
-        assert!(prod_deps
+        assert!(old[&DepCategory::Production]
             .iter()
             .all(|OldPkgDetails { name, .. }| name != "wrong-info"));
 
+        assert!(unchecked
+            .iter()
+            .any(|UncheckedPkg(PkgNameAndVersion(name, _), _)| name == "wrong-info"));
+
         Ok(())
     }
 
@@ -443,7 +762,14 @@ mod tests {
 
         let mut bytes = Vec::new();
 
-        get_output((&in1, &in2), &mut bytes, true)?;
+        get_output(
+            &DepsByCategory::from([
+                (DepCategory::Production, in1.clone()),
+                (DepCategory::Development, in2),
+            ]),
+            None,
+            &mut bytes,
+        )?;
 
         let output = String::from_utf8(bytes).unwrap();
 
@@ -451,7 +777,11 @@ mod tests {
 
         let mut bytes = Vec::new();
 
-        get_output((&in1, &in2), &mut bytes, false)?;
+        get_output(
+            &DepsByCategory::from([(DepCategory::Production, in1)]),
+            None,
+            &mut bytes,
+        )?;
 
         let output = String::from_utf8(bytes).unwrap();
 
@@ -460,6 +790,165 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_return_output_for_old_transitive_pkgs() -> Result<(), std::io::Error> {
+        let transitive = vec![(
+            OLD_PKG_DETAILS[0].clone(),
+            vec!["a".to_owned(), "b".to_owned()],
+        )];
+
+        let mut bytes = Vec::new();
+
+        get_output(
+            &DepsByCategory::from([(DepCategory::Production, OLD_PKG_DETAILS[1..].to_vec())]),
+            Some(&transitive),
+            &mut bytes,
+        )?;
+
+        let output = String::from_utf8(bytes).unwrap();
+
+        assert_eq!(output, "\n  production:\n\n    old2@0.0.1 (01/01/2016)\n        -> 7 years old, 7 older than latest\n            -> latest @0.0.1 (14/06/2023)\n\n  transitive:\n\n    old1@0.0.1 (01/01/2000)\n        -> 23 years old, 23 older than latest\n            -> latest @0.0.1 (14/06/2023)\n            -> required by a, b\n\n  total: 1 old production dependencies, 1 old transitive dependency\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_return_output_for_old_pkgs_of_every_category() -> Result<(), std::io::Error> {
+        let mut bytes = Vec::new();
+
+        get_output(
+            &DepsByCategory::from([
+                (DepCategory::Production, vec![]),
+                (DepCategory::Optional, OLD_PKG_DETAILS[1..].to_vec()),
+                (DepCategory::Peer, vec![]),
+            ]),
+            None,
+            &mut bytes,
+        )?;
+
+        let output = String::from_utf8(bytes).unwrap();
+
+        assert_eq!(output, "\n  optional:\n\n    old2@0.0.1 (01/01/2016)\n        -> 7 years old, 7 older than latest\n            -> latest @0.0.1 (14/06/2023)\n\n  total: 0 old production dependencies, 1 old optional dependency, 0 old peer dependencies\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_serialize_dates_in_rfc_3339() -> Result<(), serde_json::Error> {
+        let json = serde_json::to_value(&OLD_PKG_DETAILS[0])?;
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "old1",
+                "local_version": "0.0.1",
+                "publication_local_version": "2000-01-01T00:00:00-05:00",
+                "age_local_version": 23,
+                "latest_version": "0.0.1",
+                "publication_latest_version": "2023-06-14T19:46:38Z",
+                "age_latest_version": 0
+            })
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_filter_old_pkgs_from_evaluations() {
+        let old = PkgNameAndVersion("old1".to_owned(), "0.0.1".to_owned());
+        let recent = PkgNameAndVersion("recent".to_owned(), "1.0.0".to_owned());
+        let broken = PkgNameAndVersion("broken".to_owned(), "1.0.0".to_owned());
+        let missing = PkgNameAndVersion("missing".to_owned(), "1.0.0".to_owned());
+
+        let evaluations = Evaluations::from([
+            (old.clone(), Ok(Some(OLD_PKG_DETAILS[0].clone()))),
+            (recent.clone(), Ok(None)),
+            (
+                broken.clone(),
+                Err(Arc::new(DebsError::PackageNotFound("broken".to_owned()))),
+            ),
+            (
+                missing.clone(),
+                Err(Arc::new(DebsError::NotInCache("missing".to_owned()))),
+            ),
+        ]);
+
+        let (old_by_category, unchecked) = filter_old_packages(
+            &evaluations,
+            &DepsByCategory::from([
+                (
+                    DepCategory::Production,
+                    vec![old.clone(), recent, broken.clone()],
+                ),
+                (DepCategory::Development, vec![missing.clone()]),
+            ]),
+        );
+
+        assert_eq!(
+            old_by_category,
+            DepsByCategory::from([
+                (DepCategory::Production, vec![OLD_PKG_DETAILS[0].clone()]),
+                (DepCategory::Development, vec![]),
+            ])
+        );
+        assert_eq!(
+            unchecked,
+            vec![
+                UncheckedPkg(
+                    broken,
+                    Arc::new(DebsError::PackageNotFound("broken".to_owned()))
+                ),
+                UncheckedPkg(
+                    missing,
+                    Arc::new(DebsError::NotInCache("missing".to_owned()))
+                ),
+            ]
+        );
+
+        let (transitive_old, _) =
+            filter_transitive(&[TransitivePkg(old, vec!["a".to_owned()])], |pkg| {
+                evaluation(&evaluations, pkg)
+            });
+
+        assert_eq!(
+            transitive_old,
+            vec![(OLD_PKG_DETAILS[0].clone(), vec!["a".to_owned()])]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_report_pkgs_missing_from_offline_cache() -> Result<(), Box<dyn Error>> {
+        // offline without a cache: nothing can be checked
+        let registry = Registry::default().with_offline(true);
+
+        let pkg1 = PkgNameAndVersion("react".to_owned(), "18.2.0".to_owned());
+        let pkg2 = PkgNameAndVersion("file-loader".to_owned(), "1.1.11".to_owned());
+
+        let evaluations = evaluate_packages(&registry, 0, [&pkg1, &pkg2].into_iter()).await;
+
+        let (old, unchecked) = filter_old_packages(
+            &evaluations,
+            &DepsByCategory::from([
+                (DepCategory::Production, vec![pkg1.clone()]),
+                (DepCategory::Development, vec![pkg2.clone()]),
+            ]),
+        );
+
+        assert!(old.values().all(Vec::is_empty));
+        assert_eq!(
+            unchecked,
+            vec![
+                UncheckedPkg(pkg1, Arc::new(DebsError::NotInCache("react".to_owned()))),
+                UncheckedPkg(
+                    pkg2,
+                    Arc::new(DebsError::NotInCache("file-loader".to_owned()))
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn should_return_formatted_output_for_old_pkgs() -> Result<(), std::io::Error> {
         let mut bytes = Vec::new();
@@ -473,10 +962,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_return_output_for_old_pkgs_newer_than_latest() -> Result<(), std::io::Error> {
+        let pkg = OldPkgDetails {
+            name: "old1".to_owned(),
+            local_version: "2.0.0".to_owned(),
+            age_local_version: 5,
+            latest_version: "1.9.9".to_owned(),
+            age_latest_version: 6,
+            ..OLD_PKG_DETAILS[0].clone()
+        };
+
+        let mut bytes = Vec::new();
+
+        get_pkgs_output(&[pkg], None, &mut bytes)?;
+
+        let output = String::from_utf8(bytes).unwrap();
+
+        assert_eq!(output, "\n  old1@2.0.0 (01/01/2000)\n      -> 5 years old, 0 older than latest\n          -> latest @1.9.9 (14/06/2023)\n");
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn should_get_last_update() -> Result<(), Box<dyn Error>> {
-        let PkgAgeDetails(pkg, _, last_update, ..) =
-            to_pkg_date_tuple(PkgNameAndVersion("react".to_owned(), "18.2.0".to_owned())).await?;
+        let PkgAgeDetails(pkg, _, last_update, ..) = to_pkg_date_tuple(
+            &Registry::default(),
+            PkgNameAndVersion("react".to_owned(), "18.2.0".to_owned()),
+        )
+        .await?;
 
         assert_eq!(pkg, "react");
 
@@ -487,25 +1001,31 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn should_fail_to_get_last_update_of_fake_pkg() -> Result<(), Box<dyn Error>> {
-        let res =
-            to_pkg_date_tuple(PkgNameAndVersion("ReAcT".to_owned(), "18.2.0".to_owned())).await;
+        let res = to_pkg_date_tuple(
+            &Registry::default(),
+            PkgNameAndVersion("ReAcT".to_owned(), "18.2.0".to_owned()),
+        )
+        .await;
 
-        assert!(res
-            .expect_err("Should be an error")
-            .to_string()
-            .contains("ReAcT: missing field `name`"));
+        assert!(matches!(
+            res.expect_err("Should be an error"),
+            DebsError::PackageNotFound(pkg) if pkg == "ReAcT"
+        ));
 
         Ok(())
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn should_fail_to_get_last_update_of_bad_version() -> Result<(), Box<dyn Error>> {
-        let res =
-            to_pkg_date_tuple(PkgNameAndVersion("react".to_owned(), "A.2.0".to_owned())).await;
+        let res = to_pkg_date_tuple(
+            &Registry::default(),
+            PkgNameAndVersion("react".to_owned(), "A.2.0".to_owned()),
+        )
+        .await;
 
         assert_eq!(
             res.expect_err("Should be an error").to_string(),
-            "Version A.2.0 of react not found in timetable"
+            "react@A.2.0: version not found in the registry"
         );
 
         Ok(())
@@ -513,8 +1033,58 @@ mod tests {
 
     #[test]
     fn should_add_info_about_latest_version() {
-        let old_data = add_latest_version_info(VEC_PKG_AGE.to_vec());
+        let (old_data, unchecked) = add_latest_version_info(VEC_PKG_AGE.to_vec());
 
         assert_eq!(old_data, OLD_PKG_DETAILS.to_vec());
+        assert!(unchecked.is_empty());
+    }
+
+    #[test]
+    fn should_report_pkgs_with_incomplete_metadata() {
+        let mut unpublished = VEC_PKG_AGE[0].clone();
+        unpublished.4.dist_tags.clear();
+
+        let mut bad_date = VEC_PKG_AGE[1].clone();
+        bad_date
+            .4
+            .time
+            .insert("modified".to_owned(), "yesterday".to_owned());
+
+        let (old_data, unchecked) = add_latest_version_info(vec![unpublished, bad_date]);
+
+        assert!(old_data.is_empty());
+        assert_eq!(
+            unchecked,
+            vec![
+                UncheckedPkg(
+                    PkgNameAndVersion("old1".to_owned(), "0.0.1".to_owned()),
+                    Arc::new(DebsError::MissingMetadata {
+                        pkg: "old1".to_owned(),
+                        field: "dist-tags.latest".to_owned()
+                    })
+                ),
+                UncheckedPkg(
+                    PkgNameAndVersion("old2".to_owned(), "0.0.1".to_owned()),
+                    Arc::new(DebsError::InvalidDate {
+                        pkg: "old2".to_owned(),
+                        date: "yesterday".to_owned()
+                    })
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_publication_dates() {
+        assert!(parse_publication_date("a", "2015-03-24T00:12:24.039Z").is_ok());
+        assert!(matches!(
+            parse_publication_date("a", "24/03/2015"),
+            Err(DebsError::InvalidDate { .. })
+        ));
+        // in the future
+        assert!(matches!(
+            parse_publication_date("a", "2999-01-01T00:00:00Z"),
+            Err(DebsError::InvalidDate { .. })
+        ));
     }
 }
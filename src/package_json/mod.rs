@@ -0,0 +1,1921 @@
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+mod pnpm;
+mod spec;
+mod yarn;
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::error::DebsError;
+use crate::semver::{Range, SemVer};
+use crate::types::{
+    DepCategory, DepsByCategory, PkgName, PkgNameAndVersion, StaleLockPkg, TransitivePkg,
+    UnmetPeerPkg, Version,
+};
+pub use pnpm::pnpm_workspace_patterns;
+pub use spec::{DepSpec, NonRegistryPkg};
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PackageJson {
+    workspaces: Option<WorkspacesField>,
+    dependencies: Option<HashMap<PkgName, Version>>,
+    #[serde(rename = "devDependencies")]
+    dev_dependencies: Option<HashMap<PkgName, Version>>,
+    #[serde(rename = "optionalDependencies")]
+    optional_dependencies: Option<HashMap<PkgName, Version>>,
+    #[serde(rename = "peerDependencies")]
+    peer_dependencies: Option<HashMap<PkgName, Version>>,
+    // npm accepts both spellings
+    #[serde(rename = "bundledDependencies", alias = "bundleDependencies")]
+    bundled_dependencies: Option<BundledField>,
+}
+
+/// The `bundledDependencies` field is either a list of dependency names,
+/// or `true` to bundle every dependency.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+enum BundledField {
+    List(Vec<PkgName>),
+    All(bool),
+}
+
+/// The `workspaces` field is either a list of paths or globs (npm),
+/// or an object with such a list in its `packages` field (Yarn).
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+enum WorkspacesField {
+    List(Vec<String>),
+    Object { packages: Vec<String> },
+}
+
+impl PackageJson {
+    /// Workspace paths and globs, as written in the `workspaces` field. `None` for projects without workspaces.
+    pub fn workspace_patterns(&self) -> Option<&[String]> {
+        match &self.workspaces {
+            Some(WorkspacesField::List(patterns)) => Some(patterns),
+            Some(WorkspacesField::Object { packages }) => Some(packages),
+            None => None,
+        }
+    }
+
+    /// Spec of each dependency, whatever its category.
+    ///
+    /// Names listed in several fields keep the spec of the first one among
+    /// `dependencies`, `optionalDependencies`, `devDependencies` and `peerDependencies`.
+    pub fn dep_specs(&self) -> HashMap<PkgName, DepSpec> {
+        let mut specs = HashMap::new();
+
+        for deps in [
+            &self.dependencies,
+            &self.optional_dependencies,
+            &self.dev_dependencies,
+            &self.peer_dependencies,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            for (name, spec) in deps {
+                specs
+                    .entry(name.clone())
+                    .or_insert_with(|| DepSpec::parse(spec));
+            }
+        }
+
+        specs
+    }
+}
+
+/// package-lock.json, where installed packages are listed in `packages` by install path (e.g. `node_modules/a`).
+///
+/// npm 6 (`lockfileVersion: 1`) only writes the nested `dependencies` tree,
+/// which [`parse_package_lock`] turns into `packages` entries.
+#[derive(Clone, Debug, Deserialize)]
+struct PackageLockJson {
+    #[serde(rename = "lockfileVersion")]
+    lockfile_version: Option<u32>,
+    #[serde(default)]
+    packages: HashMap<String, PackageLockDepInfo>,
+    #[serde(default)]
+    dependencies: HashMap<PkgName, PackageLockV1DepInfo>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct PackageLockDepInfo {
+    // only set by npm for some packages (e.g. workspaces), always set for Yarn packages
+    name: Option<PkgName>,
+    version: Option<String>,
+    // for links (e.g. to workspaces), the path to the linked folder
+    resolved: Option<String>,
+    #[serde(default)]
+    link: bool,
+    #[serde(default)]
+    dependencies: HashMap<PkgName, String>,
+    #[serde(default, rename = "optionalDependencies")]
+    optional_dependencies: HashMap<PkgName, String>,
+    #[serde(default, rename = "peerDependencies")]
+    peer_dependencies: HashMap<PkgName, String>,
+    #[serde(default, rename = "devDependencies")]
+    dev_dependencies: HashMap<PkgName, String>,
+}
+
+impl PackageLockDepInfo {
+    /// Range of `pkg_name` in the dependencies of this package, whatever their kind.
+    fn dependency_range(&self, pkg_name: &str) -> Option<&str> {
+        self.dependencies
+            .get(pkg_name)
+            .or_else(|| self.optional_dependencies.get(pkg_name))
+            .or_else(|| self.peer_dependencies.get(pkg_name))
+            .or_else(|| self.dev_dependencies.get(pkg_name))
+            .map(String::as_str)
+    }
+}
+
+/// Packages installed in a project, read from the lockfile of its package manager.
+///
+/// npm records where each package is installed, so its packages are keyed by install path (e.g. `node_modules/a`)
+/// and dependencies are found by walking up `node_modules/` folders, see [`node_modules_paths`].
+///
+/// Yarn records which version each `<name>@<range>` (a descriptor) resolves to,
+/// so its packages are keyed by `<name>@<version>`, and dependencies are found through their range.
+/// Workspaces are keyed by their path relative to the root (`""` for the root package) in both cases.
+/// Read once per run with [`parse_lockfile`], and shared by every workspace.
+#[derive(Clone, Debug, Default)]
+pub struct Lockfile {
+    packages: HashMap<String, PackageLockDepInfo>,
+    /// Yarn only: descriptor -> key of the package it resolves to.
+    descriptors: Option<HashMap<String, String>>,
+}
+
+impl From<HashMap<String, PackageLockDepInfo>> for Lockfile {
+    fn from(packages: HashMap<String, PackageLockDepInfo>) -> Self {
+        Lockfile {
+            packages,
+            descriptors: None,
+        }
+    }
+}
+
+impl Lockfile {
+    /// Key of the package installed for `pkg_name` when required from the package (or workspace) at `from`.
+    ///
+    /// Unlike [`resolve_install_path`], npm links (e.g. to workspaces) are not followed.
+    fn installed(&self, from: &str, pkg_name: &str) -> Option<String> {
+        match &self.descriptors {
+            None => node_modules_paths(from, pkg_name)
+                .into_iter()
+                .find(|path| self.packages.contains_key(path)),
+            Some(descriptors) => {
+                let range = self
+                    .packages
+                    .get(&workspace_key(from))?
+                    .dependency_range(pkg_name)?;
+
+                yarn::resolve_descriptor(descriptors, pkg_name, range)
+            }
+        }
+    }
+
+    /// Name and version of the registry package at `key`.
+    ///
+    /// `None` for workspaces, linked folders and packages installed from git or local files,
+    /// which are not installed from the registry.
+    fn registry_pkg(&self, key: &str) -> Option<PkgNameAndVersion> {
+        let info = self.packages.get(key)?;
+
+        if info.resolved.as_deref().is_some_and(|resolved| {
+            matches!(DepSpec::parse(resolved), DepSpec::Git(_) | DepSpec::File(_))
+        }) {
+            return None;
+        }
+
+        let name = match &self.descriptors {
+            None => {
+                let installed_as = key
+                    .rfind("node_modules/")
+                    .map(|i| &key[i + "node_modules/".len()..])?;
+
+                // npm records the real name of aliased packages, installed under their alias
+                info.name.as_deref().unwrap_or(installed_as)
+            }
+            Some(_) => info.name.as_deref()?,
+        };
+
+        Some(PkgNameAndVersion(name.to_owned(), info.version.clone()?))
+    }
+}
+
+/// `./apps/web/` -> `apps/web`, the key of the workspace in [`Lockfile::packages`]
+fn workspace_key(workspace: &str) -> String {
+    workspace
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// YAML scalars of yarn.lock (Berry) and pnpm-lock.yaml as strings,
+/// since unquoted ranges and versions such as `2` are read as numbers.
+fn yaml_string(value: &serde_yaml::Value) -> Option<String> {
+    match value {
+        serde_yaml::Value::String(value) => Some(value.clone()),
+        serde_yaml::Value::Number(value) => Some(value.to_string()),
+        serde_yaml::Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Entry of the `dependencies` tree of package-lock.json v1,
+/// where the packages installed in `node_modules/` of a package are nested in its own `dependencies`.
+#[derive(Clone, Debug, Deserialize)]
+struct PackageLockV1DepInfo {
+    // `file:<path>` for linked folders, `npm:<name>@<version>` for aliases,
+    // and the URL of git repositories and tarballs
+    version: Option<String>,
+    // every dependency, optional ones included
+    #[serde(default)]
+    requires: HashMap<PkgName, String>,
+    #[serde(default)]
+    dependencies: HashMap<PkgName, PackageLockV1DepInfo>,
+}
+
+/// Names of the direct dependencies listed in the package.json at `json_path`, by category.
+///
+/// Each category is returned, even when empty. A dependency is listed in a single category
+/// among production, optional and bundled ones: bundled dependencies are left out of the others,
+/// and, like npm, `optionalDependencies` override `dependencies` of the same name.
+pub fn get_deps_names(json_path: &Path) -> Result<DepsByCategory<PkgName>, DebsError> {
+    Ok(categorize_deps(&parse_package_json(json_path)?))
+}
+
+fn categorize_deps(pkg_json: &PackageJson) -> DepsByCategory<PkgName> {
+    let prod = pkg_json.dependencies.clone().unwrap_or_default();
+    let optional = pkg_json.optional_dependencies.clone().unwrap_or_default();
+
+    let bundled: Vec<PkgName> = match &pkg_json.bundled_dependencies {
+        Some(BundledField::List(names)) => names.clone(),
+        Some(BundledField::All(true)) => prod.keys().cloned().collect(),
+        Some(BundledField::All(false)) | None => vec![],
+    };
+
+    let prod = prod
+        .into_keys()
+        .filter(|name| !optional.contains_key(name) && !bundled.contains(name))
+        .collect();
+
+    let optional = optional
+        .into_keys()
+        .filter(|name| !bundled.contains(name))
+        .collect();
+
+    DepsByCategory::from([
+        (DepCategory::Production, prod),
+        (
+            DepCategory::Development,
+            pkg_json
+                .dev_dependencies
+                .iter()
+                .flat_map(HashMap::keys)
+                .cloned()
+                .collect(),
+        ),
+        (DepCategory::Optional, optional),
+        (
+            DepCategory::Peer,
+            pkg_json
+                .peer_dependencies
+                .iter()
+                .flat_map(HashMap::keys)
+                .cloned()
+                .collect(),
+        ),
+        (DepCategory::Bundled, bundled),
+    ])
+}
+
+/// Retrieves the version of packages read from the root lockfile, see [`parse_lockfile`].
+///
+/// This is useful for mono-repo structures, where dependencies' metadata
+/// is not present in the root package-lock's `dependencies` field.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **pkg_json:**  | The workspace's package.json. |
+/// | **workspace:** | Path to the workspace's package.json, relative to the root package.json. |
+/// | **lock:**      | Root lockfile. |
+pub fn get_deps_version(
+    pkg_json: &PackageJson,
+    workspace: &str,
+    lock: &Lockfile,
+) -> DepsByCategory<PkgNameAndVersion> {
+    let specs = pkg_json.dep_specs();
+
+    let deps_lists = categorize_deps(pkg_json);
+
+    deps_lists
+        .into_iter()
+        .map(|(category, names)| {
+            // packages from git, local folders, etc. are unknown to the registry
+            let names = names
+                .into_iter()
+                .filter(|name| specs.get(name).is_none_or(DepSpec::is_registry))
+                .collect();
+
+            let deps = combine_deps_name_version(lock, names, workspace)
+                .into_iter()
+                .map(|PkgNameAndVersion(name, version)| match specs.get(&name) {
+                    // aliases are installed under their own name, but known to the registry under the real one
+                    Some(DepSpec::Alias {
+                        name: real_name, ..
+                    }) => PkgNameAndVersion(real_name.clone(), version),
+                    _ => PkgNameAndVersion(name, version),
+                })
+                .collect();
+
+            (category, deps)
+        })
+        .collect()
+}
+
+/// Retrieves the direct dependencies of a workspace which are not installed from the registry
+/// (git repositories, local folders and tarball URLs), and can't be checked.
+///
+/// Dependencies on other workspaces (`workspace:`) are left out, as workspaces are checked on their own.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **pkg_json:**   | The workspace's package.json. |
+/// | **categories:** | Categories of dependencies to look through. |
+pub fn get_non_registry_deps(
+    pkg_json: &PackageJson,
+    categories: &[DepCategory],
+) -> Vec<NonRegistryPkg> {
+    let specs = pkg_json.dep_specs();
+
+    let mut non_registry: Vec<NonRegistryPkg> = categorize_deps(pkg_json)
+        .into_iter()
+        .filter(|(category, _)| categories.contains(category))
+        .flat_map(|(_, names)| names)
+        .filter_map(|name| match specs.get(&name) {
+            Some(DepSpec::Workspace(_)) | None => None,
+            Some(spec) if spec.is_registry() => None,
+            Some(spec) => Some(NonRegistryPkg(name, spec.clone())),
+        })
+        .collect();
+
+    non_registry.sort_unstable_by(|NonRegistryPkg(a, _), NonRegistryPkg(b, _)| a.cmp(b));
+    non_registry.dedup();
+
+    non_registry
+}
+
+/// Retrieves the direct dependencies of a workspace whose installed version doesn't satisfy the range
+/// declared in its package.json, as happens when the lockfile is stale or was edited by hand.
+///
+/// Dependencies which are not installed are reported too, except optional and peer dependencies which may
+/// be left out on purpose. Specs which are not ranges (e.g. tags such as `latest`, or git URLs) are not checked.
+/// Only categories with such dependencies are returned.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **root:**       | Path to the root package.json. |
+/// | **workspace:**  | Path to the workspace's package.json, relative to `root`. |
+/// | **lock:**       | Root lockfile. |
+/// | **categories:** | Categories of dependencies to look through. |
+pub fn get_stale_lock_deps(
+    root: &Path,
+    workspace: &str,
+    lock: &Lockfile,
+    categories: &[DepCategory],
+) -> Result<DepsByCategory<StaleLockPkg>, DebsError> {
+    let pkg_json = parse_package_json(&root.join(workspace))?;
+
+    let specs = pkg_json.dep_specs();
+
+    let mut deps_lists = categorize_deps(&pkg_json);
+    deps_lists.retain(|category, _| categories.contains(category));
+
+    Ok(find_stale_lock_deps(lock, workspace, deps_lists, &specs))
+}
+
+fn find_stale_lock_deps(
+    lock: &Lockfile,
+    workspace: &str,
+    deps_lists: DepsByCategory<PkgName>,
+    specs: &HashMap<PkgName, DepSpec>,
+) -> DepsByCategory<StaleLockPkg> {
+    deps_lists
+        .into_iter()
+        .map(|(category, names)| {
+            let mut stale: Vec<StaleLockPkg> = names
+                .into_iter()
+                .filter_map(|name| {
+                    let range = match specs.get(&name)? {
+                        DepSpec::Registry(range) | DepSpec::Alias { range, .. } => range.clone(),
+                        _ => return None,
+                    };
+
+                    let parsed_range = Range::parse(&range)?;
+
+                    // aliases are installed under their own name, so no renaming is needed here,
+                    // and links to other workspaces are followed to the version of the workspace
+                    let version = resolve_install_path(lock, workspace, &name)
+                        .and_then(|key| lock.packages[&key].version.clone());
+
+                    match version {
+                        None if matches!(category, DepCategory::Optional | DepCategory::Peer) => {
+                            None
+                        }
+                        None => Some(StaleLockPkg(name, range, None)),
+                        Some(version) => {
+                            // versions which are not semver (e.g. commit hashes) can't be compared
+                            let is_satisfied = SemVer::parse(&version)
+                                .is_none_or(|parsed| parsed_range.satisfies(&parsed));
+
+                            (!is_satisfied).then_some(StaleLockPkg(name, range, Some(version)))
+                        }
+                    }
+                })
+                .collect();
+
+            stale.sort_unstable_by(|StaleLockPkg(a, ..), StaleLockPkg(b, ..)| a.cmp(b));
+
+            (category, stale)
+        })
+        .filter(|(_, stale)| !stale.is_empty())
+        .collect()
+}
+
+/// Retrieves the peer dependencies of a workspace whose range no version installed in the project satisfies,
+/// whether the package is installed at another version or not installed at all.
+///
+/// Peer dependencies whose range can't be read (e.g. tags such as `latest`) are not reported.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **pkg_json:** | The workspace's package.json. |
+/// | **lock:**     | Root lockfile. |
+pub fn get_unmet_peer_deps(pkg_json: &PackageJson, lock: &Lockfile) -> Vec<UnmetPeerPkg> {
+    find_unmet_peer_deps(lock, pkg_json.peer_dependencies.clone().unwrap_or_default())
+}
+
+fn find_unmet_peer_deps(
+    lock: &Lockfile,
+    peer_deps: HashMap<PkgName, Version>,
+) -> Vec<UnmetPeerPkg> {
+    // name -> installed versions
+    let mut installed: HashMap<PkgName, BTreeSet<Version>> = HashMap::new();
+
+    for key in lock.packages.keys() {
+        if let Some(PkgNameAndVersion(name, version)) = lock.registry_pkg(key) {
+            installed.entry(name).or_default().insert(version);
+        }
+    }
+
+    let mut unmet: Vec<UnmetPeerPkg> = peer_deps
+        .into_iter()
+        .filter_map(|(name, range)| {
+            let parsed_range = Range::parse(&range)?;
+
+            let versions: Vec<Version> = installed
+                .get(&name)
+                .into_iter()
+                .flatten()
+                .cloned()
+                .collect();
+
+            let is_met = versions.iter().any(|version| {
+                SemVer::parse(version).is_some_and(|version| parsed_range.satisfies(&version))
+            });
+
+            (!is_met).then_some(UnmetPeerPkg(name, range, versions))
+        })
+        .collect();
+
+    unmet.sort_unstable_by(|UnmetPeerPkg(a, ..), UnmetPeerPkg(b, ..)| a.cmp(b));
+
+    unmet
+}
+
+/// Augment a list of dependency names with their version in the current project.
+///
+/// Each package is resolved the way its package manager does, see [`Lockfile::installed`].
+/// Packages which are not installed are left out, as well as workspaces and linked folders.
+fn combine_deps_name_version(
+    lock: &Lockfile,
+    deps_list: Vec<PkgName>,
+    workspace: &str,
+) -> Vec<PkgNameAndVersion> {
+    deps_list
+        .into_iter()
+        .filter_map(|pkg_name| {
+            lock.installed(workspace, &pkg_name)
+                .and_then(|key| lock.packages[&key].version.clone())
+                .map(|version| PkgNameAndVersion(pkg_name, version))
+        })
+        .collect()
+}
+
+/// Retrieves the packages installed through the direct dependencies of a workspace (transitive dependencies),
+/// along with the direct dependencies pulling each of them in.
+///
+/// Each name@version is returned once, even when installed at several places,
+/// and packages which are also direct dependencies are left out.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **pkg_json:**    | The workspace's package.json. |
+/// | **workspace:**   | Path to the workspace's package.json, relative to the root package.json. |
+/// | **lock:**        | Root lockfile. |
+/// | **direct_deps:** | Direct dependencies of the workspace to start from. |
+pub fn get_transitive_deps(
+    pkg_json: &PackageJson,
+    workspace: &str,
+    lock: &Lockfile,
+    direct_deps: &[PkgNameAndVersion],
+) -> Vec<TransitivePkg> {
+    // registry name -> name the package is installed under
+    let aliases: HashMap<PkgName, PkgName> = pkg_json
+        .dep_specs()
+        .into_iter()
+        .filter_map(|(installed_as, spec)| match spec {
+            DepSpec::Alias { name, .. } => Some((name, installed_as)),
+            _ => None,
+        })
+        .collect();
+
+    find_transitive_deps(lock, workspace, direct_deps, &aliases)
+}
+
+fn find_transitive_deps(
+    lock: &Lockfile,
+    workspace: &str,
+    direct_deps: &[PkgNameAndVersion],
+    aliases: &HashMap<PkgName, PkgName>,
+) -> Vec<TransitivePkg> {
+    let direct_paths: Vec<(&PkgName, String)> = direct_deps
+        .iter()
+        .filter_map(|PkgNameAndVersion(name, _)| {
+            let installed_as = aliases.get(name).unwrap_or(name);
+
+            resolve_install_path(lock, workspace, installed_as).map(|path| (name, path))
+        })
+        .collect();
+
+    // install path -> direct dependencies pulling it in
+    let mut required_by: HashMap<&str, Vec<&PkgName>> = HashMap::new();
+
+    for (direct_name, direct_path) in &direct_paths {
+        let mut visited = HashSet::from([direct_path.clone()]);
+        let mut to_visit = vec![direct_path.clone()];
+
+        while let Some(path) = to_visit.pop() {
+            let info = &lock.packages[&path];
+
+            // optional dependencies may not be installed, in which case they are not resolved
+            for dep_name in info
+                .dependencies
+                .keys()
+                .chain(info.optional_dependencies.keys())
+            {
+                if let Some(dep_path) = resolve_install_path(lock, &path, dep_name) {
+                    if visited.insert(dep_path.clone()) {
+                        to_visit.push(dep_path);
+                    }
+                }
+            }
+        }
+
+        for path in visited {
+            // getting the `&str` owned by `lock`, which outlives `visited`
+            if let Some((path, _)) = lock.packages.get_key_value(&path) {
+                required_by.entry(path).or_default().push(direct_name);
+            }
+        }
+    }
+
+    // the same name@version can be installed at several places
+    let mut transitive: BTreeMap<PkgNameAndVersion, BTreeSet<PkgName>> = BTreeMap::new();
+
+    for (path, direct_names) in required_by {
+        // links' targets (e.g. `common` for a workspace) are not installed from the registry
+        let Some(pkg) = lock.registry_pkg(path) else {
+            continue;
+        };
+
+        if direct_deps.contains(&pkg) {
+            continue;
+        }
+
+        transitive
+            .entry(pkg)
+            .or_default()
+            .extend(direct_names.into_iter().cloned());
+    }
+
+    transitive
+        .into_iter()
+        .map(|(pkg, direct_names)| TransitivePkg(pkg, direct_names.into_iter().collect()))
+        .collect()
+}
+
+/// Retrieves every chain of dependencies leading from a workspace to the installed versions of a package,
+/// each starting with a direct dependency of the workspace and ending with the package itself.
+///
+/// Dependencies are followed through the `dependencies`, `optionalDependencies` and `peerDependencies`
+/// of the lockfile's packages. Chains are sorted, and never go through the same package twice.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **root:**      | Path to the root package.json. |
+/// | **workspace:** | Path to the workspace's package.json, relative to `root`. |
+/// | **lock:**      | Root lockfile. |
+/// | **pkg_name:**  | Name of the package to look for. |
+/// | **version:**    | Version of the package to look for, or `None` for any installed version. |
+/// | **categories:** | Categories of direct dependencies of the workspace to start from. |
+pub fn get_dependency_paths(
+    root: &Path,
+    workspace: &str,
+    lock: &Lockfile,
+    pkg_name: &str,
+    version: Option<&str>,
+    categories: &[DepCategory],
+) -> Result<Vec<Vec<PkgNameAndVersion>>, DebsError> {
+    let direct_names = get_deps_names(&root.join(workspace))?
+        .into_iter()
+        .filter(|(category, _)| categories.contains(category))
+        .flat_map(|(_, names)| names)
+        .collect::<Vec<PkgName>>();
+
+    Ok(find_dependency_paths(
+        lock,
+        workspace,
+        &direct_names,
+        (pkg_name, version),
+    ))
+}
+
+/// Dependencies of each package of the lockfile:
+/// key -> (name required, key of the dependency).
+///
+/// Dependencies which are not installed, e.g. unmet optional or peer dependencies, are left out.
+fn dependency_graph(lock: &Lockfile) -> HashMap<&str, Vec<(&str, String)>> {
+    lock.packages
+        .iter()
+        .map(|(path, info)| {
+            let deps = info
+                .dependencies
+                .keys()
+                .chain(info.optional_dependencies.keys())
+                .chain(info.peer_dependencies.keys())
+                .filter_map(|dep_name| {
+                    resolve_install_path(lock, path, dep_name)
+                        .map(|dep_path| (dep_name.as_str(), dep_path))
+                })
+                .collect();
+
+            (path.as_str(), deps)
+        })
+        .collect()
+}
+
+fn find_dependency_paths(
+    lock: &Lockfile,
+    workspace: &str,
+    direct_names: &[PkgName],
+    (pkg_name, version): (&str, Option<&str>),
+) -> Vec<Vec<PkgNameAndVersion>> {
+    let graph = dependency_graph(lock);
+
+    let pkg_at = |name: &str, path: &str| {
+        PkgNameAndVersion(
+            name.to_owned(),
+            lock.packages[path].version.clone().unwrap_or_default(),
+        )
+    };
+
+    let is_target = |name: &str, path: &str| {
+        name == pkg_name
+            && version.is_none_or(|version| lock.packages[path].version.as_deref() == Some(version))
+    };
+
+    // install paths from which the package can be reached, to only walk down chains leading to it
+    let mut leads_to_target: HashSet<&str> = HashSet::new();
+    let mut required_by: HashMap<&str, Vec<&str>> = HashMap::new();
+
+    for (path, deps) in &graph {
+        for (dep_name, dep_path) in deps {
+            if is_target(dep_name, dep_path) {
+                leads_to_target.insert(path);
+            }
+
+            if let Some((dep_path, _)) = lock.packages.get_key_value(dep_path) {
+                required_by.entry(dep_path).or_default().push(path);
+            }
+        }
+    }
+
+    let mut to_visit: Vec<&str> = leads_to_target.iter().copied().collect();
+
+    while let Some(path) = to_visit.pop() {
+        for dependent in required_by.get(path).into_iter().flatten() {
+            if leads_to_target.insert(dependent) {
+                to_visit.push(dependent);
+            }
+        }
+    }
+
+    /// Walks down the dependencies of the last package of `chain`, collecting the chains ending with the package.
+    fn walk<'a>(
+        graph: &'a HashMap<&str, Vec<(&str, String)>>,
+        leads_to_target: &HashSet<&str>,
+        is_target: &dyn Fn(&str, &str) -> bool,
+        chain: &mut Vec<(&'a str, &'a str)>,
+        found: &mut Vec<Vec<(&'a str, &'a str)>>,
+    ) {
+        let (name, path) = *chain.last().expect("chains start with a direct dependency");
+
+        if is_target(name, path) {
+            found.push(chain.clone());
+        }
+
+        if !leads_to_target.contains(path) {
+            return;
+        }
+
+        for (dep_name, dep_path) in graph.get(path).into_iter().flatten() {
+            // dependency cycles, e.g. through peer dependencies
+            if chain.iter().any(|(_, known)| known == dep_path) {
+                continue;
+            }
+
+            chain.push((dep_name, dep_path));
+            walk(graph, leads_to_target, is_target, chain, found);
+            chain.pop();
+        }
+    }
+
+    let direct_paths: Vec<(&str, String)> = direct_names
+        .iter()
+        .filter_map(|name| {
+            resolve_install_path(lock, workspace, name).map(|path| (name.as_str(), path))
+        })
+        .collect();
+
+    let mut found = Vec::new();
+
+    for (direct_name, direct_path) in &direct_paths {
+        walk(
+            &graph,
+            &leads_to_target,
+            &is_target,
+            &mut vec![(direct_name, direct_path)],
+            &mut found,
+        );
+    }
+
+    let mut paths: Vec<Vec<PkgNameAndVersion>> = found
+        .into_iter()
+        .map(|chain| {
+            chain
+                .into_iter()
+                .map(|(name, path)| pkg_at(name, path))
+                .collect()
+        })
+        .collect();
+
+    paths.sort_unstable();
+    paths.dedup();
+
+    paths
+}
+
+/// Key of the package installed for `pkg_name`, when required from the package (or workspace) at `from`,
+/// following npm links to workspaces.
+fn resolve_install_path(lock: &Lockfile, from: &str, pkg_name: &str) -> Option<String> {
+    let path = lock.installed(from, pkg_name)?;
+
+    match &lock.packages[&path] {
+        PackageLockDepInfo {
+            link: true,
+            resolved: Some(target),
+            ..
+        } if lock.packages.contains_key(target) => Some(target.clone()),
+        _ => Some(path),
+    }
+}
+
+/// Keys under which package-lock.json's `packages` field may hold `pkg_name` when required from the folder at `from`,
+/// from the closest to the furthest.
+///
+/// For example, for the `apps/web/` workspace: `apps/web/node_modules/<pkg_name>`, `apps/node_modules/<pkg_name>`,
+/// then `node_modules/<pkg_name>` (where hoisted dependencies end up).
+/// `from` can also be the install path of a package, such as `node_modules/a`.
+fn node_modules_paths(from: &str, pkg_name: &str) -> Vec<String> {
+    let from = from.replace('\\', "/");
+
+    // `./apps/web/` -> ["apps", "web"]
+    let segments: Vec<&str> = from
+        .split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect();
+
+    (0..=segments.len())
+        .rev()
+        // `node_modules/a/node_modules/node_modules/<pkg_name>` is not a thing
+        .filter(|depth| *depth == 0 || segments[depth - 1] != "node_modules")
+        .map(|depth| match depth {
+            0 => format!("node_modules/{pkg_name}"),
+            _ => format!("{}/node_modules/{pkg_name}", segments[..depth].join("/")),
+        })
+        .collect()
+}
+
+pub fn parse_package_json(path: &Path) -> Result<PackageJson, DebsError> {
+    parse_file(&path.join("package.json"))
+}
+
+/// Reads the lockfile next to the root package.json: package-lock.json, or else yarn.lock (classic or Berry),
+/// or else pnpm-lock.yaml.
+///
+/// `workspaces` are the workspaces about to be checked, whose dependencies Yarn classic doesn't record.
+pub fn parse_lockfile(root: &Path, workspaces: &[String]) -> Result<Lockfile, DebsError> {
+    let yarn_lock_path = root.join("yarn.lock");
+    let pnpm_lock_path = root.join("pnpm-lock.yaml");
+
+    let mut lock = if yarn_lock_path.is_file() && !root.join("package-lock.json").is_file() {
+        yarn::parse_yarn_lock(&yarn_lock_path)?
+    } else if pnpm_lock_path.is_file() && !root.join("package-lock.json").is_file() {
+        pnpm::parse_pnpm_lock(&pnpm_lock_path)?
+    } else {
+        // a missing lockfile is reported as a missing package-lock.json
+        return Ok(parse_package_lock(root)?.packages.into());
+    };
+
+    for workspace in workspaces {
+        if let Entry::Vacant(entry) = lock.packages.entry(workspace_key(workspace)) {
+            let pkg_json = parse_package_json(&root.join(workspace))?;
+
+            entry.insert(PackageLockDepInfo {
+                dependencies: pkg_json.dependencies.unwrap_or_default(),
+                optional_dependencies: pkg_json.optional_dependencies.unwrap_or_default(),
+                peer_dependencies: pkg_json.peer_dependencies.unwrap_or_default(),
+                dev_dependencies: pkg_json.dev_dependencies.unwrap_or_default(),
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(lock)
+}
+
+fn parse_package_lock(path: &Path) -> Result<PackageLockJson, DebsError> {
+    let mut lock: PackageLockJson = parse_file(&path.join("package-lock.json"))?;
+
+    // v2 lockfiles have both formats, for backwards compatibility
+    if lock.lockfile_version == Some(1) {
+        flatten_v1_dependencies("", &lock.dependencies, &mut lock.packages);
+    }
+
+    Ok(lock)
+}
+
+/// Adds the packages of a package-lock.json v1 `dependencies` tree to `packages`, under their install path.
+///
+/// `prefix` is the install path of the package owning `dependencies` followed by a `/`, or `""` for the root.
+fn flatten_v1_dependencies(
+    prefix: &str,
+    dependencies: &HashMap<PkgName, PackageLockV1DepInfo>,
+    packages: &mut HashMap<String, PackageLockDepInfo>,
+) {
+    for (name, info) in dependencies {
+        let path = format!("{prefix}node_modules/{name}");
+
+        let version = info.version.as_deref().unwrap_or_default();
+
+        let entry = match DepSpec::parse(version) {
+            // linked folders are not installed from the registry
+            DepSpec::File(target) => PackageLockDepInfo {
+                resolved: Some(
+                    target
+                        .trim_start_matches("file:")
+                        .trim_start_matches("./")
+                        .to_owned(),
+                ),
+                link: true,
+                ..Default::default()
+            },
+            // the real name is needed to look the package up in the registry, as in v2 lockfiles
+            DepSpec::Alias { name, range } => PackageLockDepInfo {
+                name: Some(name),
+                version: Some(range),
+                dependencies: info.requires.clone(),
+                ..Default::default()
+            },
+            // without a version, these are not looked up in the registry
+            DepSpec::Git(url) | DepSpec::Tarball(url) => PackageLockDepInfo {
+                resolved: Some(url),
+                dependencies: info.requires.clone(),
+                ..Default::default()
+            },
+            _ => PackageLockDepInfo {
+                version: info.version.clone(),
+                dependencies: info.requires.clone(),
+                ..Default::default()
+            },
+        };
+
+        packages.insert(path.clone(), entry);
+
+        flatten_v1_dependencies(&format!("{path}/"), &info.dependencies, packages);
+    }
+}
+
+fn parse_file<T: DeserializeOwned>(path: &Path) -> Result<T, DebsError> {
+    let file = File::open(path).map_err(|source| DebsError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    let reader = BufReader::new(file);
+
+    serde_json::from_reader(reader).map_err(|e| DebsError::json_parse(path.to_owned(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static PROD_PKGS: [&str; 76] = [
+        "react-select",
+        "react-chartjs-2",
+        "redux-thunk",
+        "copy-to-clipboard",
+        "react-helmet",
+        "papaparse",
+        "rc-calendar",
+        "react-dropzone",
+        "react-bootstrap",
+        "recompose",
+        "swr",
+        "noty",
+        "rc-tooltip",
+        "react-hover-observer",
+        "react-intl",
+        "react-toggle",
+        "react-autosize-textarea",
+        "jspdf",
+        "redux",
+        "react-lowlight",
+        "react-router",
+        "react-dom",
+        "draft-js",
+        "react-rnd",
+        "moment-timezone",
+        "markdown-draft-js",
+        "rc-dropdown",
+        "react-draft-wysiwyg",
+        "react-codemirror2",
+        "react-simple-maps",
+        "hunq",
+        "react-refresh",
+        "reselect",
+        "query-string",
+        "rc-progress",
+        "react-tooltip",
+        "react-url-query",
+        "immer",
+        "react-loadable",
+        "lodash",
+        "react-virtualized",
+        "redux-mock-store",
+        "debounce-promise",
+        "semver",
+        "react-hook-form",
+        "rc-select",
+        "rc-time-picker",
+        "uuid",
+        "prop-types",
+        "redux-form",
+        "react",
+        "react-datepicker",
+        "html2canvas",
+        "rc-input-number",
+        "prettysize",
+        "history",
+        "codemirror",
+        "d3",
+        "chartjs-plugin-datalabels",
+        "framer-motion",
+        "react-lineto",
+        "react-sizes",
+        "socket.io-client",
+        "rc-table",
+        "react-image-lightbox",
+        "react-redux",
+        "recharts",
+        "rc-slider",
+        "normalize.css",
+        "chart.js",
+        "highlight.js",
+        "iconv-lite",
+        "country-list",
+        "react-router-dom",
+        "moment",
+        "classnames",
+    ];
+
+    static DEV_PKGS: [&str; 61] = [
+        "@babel/preset-typescript",
+        "@types/react-simple-maps",
+        "@babel/core",
+        "enzyme-to-json",
+        "core-js",
+        "@types/jest",
+        "@testing-library/react-hooks",
+        "enzyme",
+        "@types/classnames",
+        "@pmmmwh/react-refresh-webpack-plugin",
+        "jest-environment-jsdom",
+        "react-svg-loader",
+        "unused-webpack-plugin",
+        "webpack-cli",
+        "@babel/cli",
+        "@types/react",
+        "postcss",
+        "terser-webpack-plugin",
+        "autoprefixer",
+        "url-loader",
+        "@babel/polyfill",
+        "babel-jest",
+        "css-loader",
+        "style-loader",
+        "optimize-css-assets-webpack-plugin",
+        "raw-loader",
+        "postcss-import",
+        "file-loader",
+        "jest-fetch-mock",
+        "@types/lodash",
+        "@babel/plugin-proposal-optional-chaining",
+        "babel-loader",
+        "html-webpack-plugin",
+        "tailwindcss",
+        "@babel/plugin-proposal-json-strings",
+        "@babel/plugin-proposal-numeric-separator",
+        "postcss-assets",
+        "react-test-renderer",
+        "@types/recharts",
+        "clean-webpack-plugin",
+        "webpack",
+        "jest",
+        "@babel/plugin-syntax-dynamic-import",
+        "typescript",
+        "@types/react-helmet",
+        "babel-plugin-lodash",
+        "html-loader",
+        "postcss-loader",
+        "worker-loader",
+        "@babel/plugin-proposal-throw-expressions",
+        "webpack-dev-server",
+        "@types/moment-timezone",
+        "cssnano",
+        "html-webpack-include-assets-plugin",
+        "ts-loader",
+        "@babel/preset-env",
+        "copy-webpack-plugin",
+        "@babel/preset-react",
+        "@wojtekmaj/enzyme-adapter-react-17",
+        "@babel/plugin-proposal-class-properties",
+        "webpack-merge",
+    ];
+
+    #[test]
+    fn should_return_deps_names() {
+        let list = get_deps_names(Path::new("test-assets/"));
+
+        assert!(list.is_ok());
+
+        let (prod, dev) = prod_and_dev(list.unwrap());
+
+        assert!(PROD_PKGS
+            .into_iter()
+            .all(|name| prod.contains(&name.to_string())));
+        assert!(DEV_PKGS
+            .into_iter()
+            .all(|name| dev.contains(&name.to_string())));
+    }
+
+    #[test]
+    fn deps_list_to_version_tuple_with_prefix_test() {
+        let deps_info = HashMap::from([
+            (
+                "node_modules/a".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("1.0.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "web/node_modules/a".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("2.0.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let lock = Lockfile::from(deps_info);
+
+        let deps_list = vec!["a".to_owned(), "b".to_owned()];
+
+        assert_eq!(
+            combine_deps_name_version(&lock, deps_list.clone(), "./"),
+            vec![PkgNameAndVersion("a".to_owned(), "1.0.0".to_owned())]
+        );
+        assert_eq!(
+            combine_deps_name_version(&lock, deps_list.clone(), "web/"),
+            vec![PkgNameAndVersion("a".to_owned(), "2.0.0".to_owned())]
+        );
+        // not installed in `api/`: hoisted to the root
+        assert_eq!(
+            combine_deps_name_version(&lock, deps_list, "api"),
+            vec![PkgNameAndVersion("a".to_owned(), "1.0.0".to_owned())]
+        );
+    }
+
+    #[test]
+    fn should_walk_up_to_root_node_modules() {
+        assert_eq!(
+            node_modules_paths("./apps/web/", "@scope/a"),
+            vec![
+                "apps/web/node_modules/@scope/a",
+                "apps/node_modules/@scope/a",
+                "node_modules/@scope/a",
+            ]
+        );
+        assert_eq!(node_modules_paths("./", "a"), vec!["node_modules/a"]);
+    }
+
+    #[test]
+    fn should_find_transitive_deps() {
+        let lock_entry = |version: &str, deps: &[&str]| PackageLockDepInfo {
+            version: Some(version.to_owned()),
+            dependencies: deps
+                .iter()
+                .map(|name| (name.to_string(), "*".to_owned()))
+                .collect(),
+            ..Default::default()
+        };
+
+        let deps_info = HashMap::from([
+            (
+                "node_modules/a".to_owned(),
+                lock_entry("1.0.0", &["c", "d"]),
+            ),
+            ("node_modules/b".to_owned(), lock_entry("1.0.0", &["c"])),
+            ("node_modules/c".to_owned(), lock_entry("1.0.0", &["a"])),
+            (
+                "node_modules/a/node_modules/d".to_owned(),
+                lock_entry("2.0.0", &[]),
+            ),
+            ("node_modules/d".to_owned(), lock_entry("1.0.0", &[])),
+            (
+                "node_modules/lib".to_owned(),
+                PackageLockDepInfo {
+                    resolved: Some("lib".to_owned()),
+                    link: true,
+                    ..Default::default()
+                },
+            ),
+            ("lib".to_owned(), lock_entry("0.0.1", &["d"])),
+        ]);
+
+        let lock = Lockfile::from(deps_info);
+
+        let direct_deps = vec![
+            PkgNameAndVersion("a".to_owned(), "1.0.0".to_owned()),
+            PkgNameAndVersion("b".to_owned(), "1.0.0".to_owned()),
+            PkgNameAndVersion("lib".to_owned(), "0.0.1".to_owned()),
+        ];
+
+        assert_eq!(
+            find_transitive_deps(&lock, "./", &direct_deps, &HashMap::new()),
+            vec![
+                // `a` is a direct dependency, even though `b` pulls it in through `c`
+                TransitivePkg(
+                    PkgNameAndVersion("c".to_owned(), "1.0.0".to_owned()),
+                    vec!["a".to_owned(), "b".to_owned()]
+                ),
+                TransitivePkg(
+                    PkgNameAndVersion("d".to_owned(), "1.0.0".to_owned()),
+                    vec!["lib".to_owned()]
+                ),
+                TransitivePkg(
+                    PkgNameAndVersion("d".to_owned(), "2.0.0".to_owned()),
+                    vec!["a".to_owned(), "b".to_owned()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_dependency_paths() {
+        let pkg =
+            |name: &str, version: &str| PkgNameAndVersion(name.to_owned(), version.to_owned());
+
+        let deps_info = HashMap::from([
+            (
+                "node_modules/a".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("1.0.0".to_owned()),
+                    dependencies: HashMap::from([("b".to_owned(), "*".to_owned())]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/b".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("1.0.0".to_owned()),
+                    // cycle through `a`, and a peer dependency which is not installed
+                    peer_dependencies: HashMap::from([
+                        ("a".to_owned(), "*".to_owned()),
+                        ("missing".to_owned(), "*".to_owned()),
+                    ]),
+                    optional_dependencies: HashMap::from([("c".to_owned(), "*".to_owned())]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/c".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("1.0.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/b/node_modules/c".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("2.0.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let lock = Lockfile::from(deps_info);
+
+        let direct_names = vec!["a".to_owned(), "c".to_owned()];
+
+        assert_eq!(
+            find_dependency_paths(&lock, "./", &direct_names, ("c", None)),
+            vec![
+                vec![pkg("a", "1.0.0"), pkg("b", "1.0.0"), pkg("c", "2.0.0")],
+                vec![pkg("c", "1.0.0")],
+            ]
+        );
+        assert_eq!(
+            find_dependency_paths(&lock, "./", &direct_names, ("c", Some("1.0.0"))),
+            vec![vec![pkg("c", "1.0.0")]]
+        );
+        assert_eq!(
+            find_dependency_paths(&lock, "./", &direct_names, ("a", None)),
+            vec![vec![pkg("a", "1.0.0")]]
+        );
+        assert!(find_dependency_paths(&lock, "./", &direct_names, ("d", None)).is_empty());
+    }
+
+    #[test]
+    fn should_find_transitive_deps_of_monorepo_workspace() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/monorepo/");
+
+        let lock = parse_lockfile(root, &["backend/".to_owned()])?;
+
+        let pkg_json = parse_package_json(&root.join("backend/"))?;
+
+        let (prod, _) = prod_and_dev(get_deps_version(&pkg_json, "backend/", &lock));
+
+        let transitive = get_transitive_deps(&pkg_json, "backend/", &lock, &prod);
+
+        assert!(transitive.iter().any(|TransitivePkg(pkg, direct_deps)| {
+            *pkg == PkgNameAndVersion("serve-static".to_owned(), "1.15.0".to_owned())
+                && direct_deps.contains(&"express".to_owned())
+        }));
+        // direct dependencies are not repeated
+        assert!(!transitive
+            .iter()
+            .any(|TransitivePkg(pkg, _)| prod.contains(pkg)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_prefer_workspace_node_modules() {
+        let frontend = parse_lock("frontend/");
+
+        // both hoisted, and installed in `frontend/node_modules/` with another version
+        assert!(frontend.contains(&PkgNameAndVersion(
+            "moment-timezone".to_owned(),
+            "0.5.35".to_owned()
+        )));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_monorepo_frontend_package_lock_test() {
+        let pkg_name_and_version = parse_lock("frontend/");
+
+        assert!(pkg_name_and_version.contains(&PkgNameAndVersion(
+            "react-refresh".to_owned(),
+            "0.8.3".to_owned()
+        )));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_monorepo_backend_package_lock_test() {
+        let pkg_name_and_version = parse_lock("backend/");
+
+        assert!(pkg_name_and_version.contains(&PkgNameAndVersion(
+            "express".to_owned(),
+            "4.18.2".to_owned()
+        )));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_monorepo_common_package_json_test() {
+        let pkg_name_and_version = parse_lock("common/");
+
+        assert!(pkg_name_and_version.contains(&PkgNameAndVersion(
+            "lodash.uniq".to_owned(),
+            "4.5.0".to_owned()
+        )));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn parse_package_lock_test() {
+        let res = parse_package_lock(Path::new("test-assets/"));
+
+        assert!(res.is_ok());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_return_error_on_missing_pkg_json() {
+        let res = parse_package_json(Path::new("missing-path/"));
+
+        assert!(res.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn should_return_error_on_missing_pkg_lock() {
+        let res = parse_package_lock(Path::new("missing-path/"));
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn should_read_root_deps_without_workspaces() -> Result<(), DebsError> {
+        let pkg_json = parse_package_json(Path::new("test-assets/"))?;
+
+        assert_eq!(pkg_json.workspace_patterns(), None);
+
+        let root = Path::new("test-assets/");
+
+        let lock = parse_lockfile(root, &["./".to_owned()])?;
+
+        let pkg_json = parse_package_json(root)?;
+
+        let (prod, dev) = prod_and_dev(get_deps_version(&pkg_json, "./", &lock));
+
+        assert!(prod.contains(&PkgNameAndVersion(
+            "noty".to_owned(),
+            "3.2.0-beta-deprecated".to_owned()
+        )));
+        assert!(!dev.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_both_workspaces_forms() -> Result<(), serde_json::Error> {
+        let npm: PackageJson = serde_json::from_str(r#"{ "workspaces": ["packages/*"] }"#)?;
+        let yarn: PackageJson = serde_json::from_str(
+            r#"{ "workspaces": { "packages": ["packages/*"], "nohoist": ["**/react"] } }"#,
+        )?;
+
+        assert_eq!(
+            npm.workspace_patterns(),
+            Some(&["packages/*".to_owned()][..])
+        );
+        assert_eq!(yarn.workspace_patterns(), npm.workspace_patterns());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_categorize_deps() -> Result<(), serde_json::Error> {
+        let pkg_json: PackageJson = serde_json::from_str(
+            r#"{
+                "dependencies": { "a": "^1.0.0", "b": "^1.0.0", "c": "^1.0.0" },
+                "devDependencies": { "d": "^1.0.0" },
+                "optionalDependencies": { "b": "^1.0.0" },
+                "peerDependencies": { "react": "^18.0.0" },
+                "bundleDependencies": ["c"]
+            }"#,
+        )?;
+
+        let deps = categorize_deps(&pkg_json);
+
+        assert_eq!(deps[&DepCategory::Production], vec!["a".to_owned()]);
+        assert_eq!(deps[&DepCategory::Development], vec!["d".to_owned()]);
+        assert_eq!(deps[&DepCategory::Optional], vec!["b".to_owned()]);
+        assert_eq!(deps[&DepCategory::Peer], vec!["react".to_owned()]);
+        assert_eq!(deps[&DepCategory::Bundled], vec!["c".to_owned()]);
+
+        // `true` bundles every dependency
+        let pkg_json: PackageJson = serde_json::from_str(
+            r#"{ "dependencies": { "a": "^1.0.0" }, "bundledDependencies": true }"#,
+        )?;
+
+        let deps = categorize_deps(&pkg_json);
+
+        assert!(deps[&DepCategory::Production].is_empty());
+        assert_eq!(deps[&DepCategory::Bundled], vec!["a".to_owned()]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_find_unmet_peer_deps() {
+        let lock = Lockfile::from(HashMap::from([
+            (
+                "node_modules/react".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("18.2.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/a/node_modules/react".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("16.14.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        let peer_deps = HashMap::from([
+            ("react".to_owned(), "^16.8.0 || ^17.0.0".to_owned()),
+            ("react-dom".to_owned(), "^18.0.0".to_owned()),
+            // not a range
+            ("other".to_owned(), "latest".to_owned()),
+        ]);
+
+        assert_eq!(
+            find_unmet_peer_deps(&lock, peer_deps),
+            vec![UnmetPeerPkg(
+                "react-dom".to_owned(),
+                "^18.0.0".to_owned(),
+                vec![]
+            )]
+        );
+
+        let peer_deps = HashMap::from([("react".to_owned(), "^17.0.0".to_owned())]);
+
+        assert_eq!(
+            find_unmet_peer_deps(&lock, peer_deps),
+            vec![UnmetPeerPkg(
+                "react".to_owned(),
+                "^17.0.0".to_owned(),
+                vec!["16.14.0".to_owned(), "18.2.0".to_owned()]
+            )]
+        );
+    }
+
+    #[test]
+    fn should_find_stale_lock_deps() {
+        let lock = Lockfile::from(HashMap::from([
+            (
+                "node_modules/express".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("4.16.4".to_owned()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/foo".to_owned(),
+                PackageLockDepInfo {
+                    name: Some("bar".to_owned()),
+                    version: Some("1.9.0".to_owned()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "node_modules/ms".to_owned(),
+                PackageLockDepInfo {
+                    version: Some("2.1.3".to_owned()),
+                    ..Default::default()
+                },
+            ),
+        ]));
+
+        let specs: HashMap<PkgName, DepSpec> = [
+            ("express", "^4.18.0"),
+            ("foo", "npm:bar@^2.0.0"),
+            ("ms", "2.1.1 - 2.1.3 || ^3"),
+            ("react", "latest"),
+            ("debug", "~4.1.0"),
+            ("fsevents", "^2.3.2"),
+        ]
+        .into_iter()
+        .map(|(name, spec)| (name.to_owned(), DepSpec::parse(spec)))
+        .collect();
+
+        let deps_lists = DepsByCategory::from([
+            (
+                DepCategory::Production,
+                vec![
+                    "react".to_owned(),
+                    "ms".to_owned(),
+                    "foo".to_owned(),
+                    "express".to_owned(),
+                ],
+            ),
+            (DepCategory::Development, vec!["debug".to_owned()]),
+            // optional dependencies may not be installed
+            (DepCategory::Optional, vec!["fsevents".to_owned()]),
+        ]);
+
+        assert_eq!(
+            find_stale_lock_deps(&lock, "./", deps_lists, &specs),
+            DepsByCategory::from([
+                (
+                    DepCategory::Production,
+                    vec![
+                        StaleLockPkg(
+                            "express".to_owned(),
+                            "^4.18.0".to_owned(),
+                            Some("4.16.4".to_owned())
+                        ),
+                        StaleLockPkg(
+                            "foo".to_owned(),
+                            "^2.0.0".to_owned(),
+                            Some("1.9.0".to_owned())
+                        ),
+                    ]
+                ),
+                (
+                    DepCategory::Development,
+                    vec![StaleLockPkg("debug".to_owned(), "~4.1.0".to_owned(), None)]
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_find_stale_lock_deps_through_workspace_links() {
+        let workspace = |version: &str| PackageLockDepInfo {
+            version: Some(version.to_owned()),
+            ..Default::default()
+        };
+        let link = |target: &str| PackageLockDepInfo {
+            resolved: Some(target.to_owned()),
+            link: true,
+            ..Default::default()
+        };
+
+        let lock = Lockfile::from(HashMap::from([
+            ("node_modules/common".to_owned(), link("packages/common")),
+            ("packages/common".to_owned(), workspace("1.2.0")),
+            ("node_modules/utils".to_owned(), link("packages/utils")),
+            ("packages/utils".to_owned(), workspace("2.0.0")),
+        ]));
+
+        let specs: HashMap<PkgName, DepSpec> = [("common", "^1.0.0"), ("utils", "^1.0.0")]
+            .into_iter()
+            .map(|(name, spec)| (name.to_owned(), DepSpec::parse(spec)))
+            .collect();
+
+        let deps_lists = DepsByCategory::from([(
+            DepCategory::Production,
+            vec!["common".to_owned(), "utils".to_owned()],
+        )]);
+
+        assert_eq!(
+            find_stale_lock_deps(&lock, "packages/app", deps_lists, &specs),
+            DepsByCategory::from([(
+                DepCategory::Production,
+                vec![StaleLockPkg(
+                    "utils".to_owned(),
+                    "^1.0.0".to_owned(),
+                    Some("2.0.0".to_owned())
+                )]
+            )])
+        );
+    }
+
+    #[test]
+    fn should_read_package_lock_v1() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/lockfile-v1/");
+
+        let lock = parse_lockfile(root, &["./".to_owned()])?;
+
+        let pkg_json = parse_package_json(root)?;
+
+        let (prod, dev) = prod_and_dev(get_deps_version(&pkg_json, "./", &lock));
+
+        // `local-lib` is a linked folder
+        assert_eq!(
+            prod,
+            vec![PkgNameAndVersion("express".to_owned(), "4.16.4".to_owned())]
+        );
+        assert_eq!(
+            dev,
+            vec![PkgNameAndVersion("debug".to_owned(), "4.1.1".to_owned())]
+        );
+
+        let transitive = get_transitive_deps(&pkg_json, "./", &lock, &prod);
+
+        // nested dependencies take precedence over hoisted ones
+        assert_eq!(
+            transitive,
+            vec![
+                TransitivePkg(
+                    PkgNameAndVersion("debug".to_owned(), "2.6.9".to_owned()),
+                    vec!["express".to_owned()]
+                ),
+                TransitivePkg(
+                    PkgNameAndVersion("ms".to_owned(), "2.0.0".to_owned()),
+                    vec!["express".to_owned()]
+                ),
+                TransitivePkg(
+                    PkgNameAndVersion("serve-static".to_owned(), "1.13.2".to_owned()),
+                    vec!["express".to_owned()]
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_package_lock_v1_specs() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/lockfile-v1-specs/");
+
+        let lock = parse_lockfile(root, &["./".to_owned()])?;
+
+        let pkg_json = parse_package_json(root)?;
+
+        let (mut prod, _) = prod_and_dev(get_deps_version(&pkg_json, "./", &lock));
+        prod.sort_unstable();
+
+        // `repo` (git) and `tarball` are not installed from the registry
+        assert_eq!(
+            prod,
+            vec![
+                PkgNameAndVersion("bar".to_owned(), "2.0.0".to_owned()),
+                PkgNameAndVersion("wrapper".to_owned(), "1.0.0".to_owned()),
+            ]
+        );
+
+        assert_eq!(
+            get_transitive_deps(&pkg_json, "./", &lock, &prod),
+            vec![TransitivePkg(
+                PkgNameAndVersion("baz".to_owned(), "1.2.0".to_owned()),
+                vec!["wrapper".to_owned()]
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_resolve_aliases_and_skip_non_registry_deps() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/specs/");
+
+        let lock = parse_lockfile(root, &["./".to_owned()])?;
+
+        let pkg_json = parse_package_json(root)?;
+
+        let (mut prod, dev) = prod_and_dev(get_deps_version(&pkg_json, "./", &lock));
+        prod.sort_unstable();
+
+        // `foo` is an alias of `bar`, the others are not installed from the registry
+        assert_eq!(
+            prod,
+            vec![
+                PkgNameAndVersion("bar".to_owned(), "2.3.1".to_owned()),
+                PkgNameAndVersion("ms".to_owned(), "2.1.3".to_owned()),
+            ]
+        );
+        assert_eq!(dev, vec![]);
+
+        // found at `node_modules/foo/node_modules/ms`
+        assert_eq!(
+            get_transitive_deps(&pkg_json, "./", &lock, &prod),
+            vec![TransitivePkg(
+                PkgNameAndVersion("ms".to_owned(), "2.0.0".to_owned()),
+                vec!["bar".to_owned()]
+            )]
+        );
+
+        let kinds = |categories: &[DepCategory]| -> Vec<(String, &str)> {
+            get_non_registry_deps(&pkg_json, categories)
+                .into_iter()
+                .map(|NonRegistryPkg(name, spec)| (name, spec.kind()))
+                .collect()
+        };
+
+        assert_eq!(
+            kinds(&[DepCategory::Production]),
+            vec![
+                ("common".to_owned(), "file"),
+                ("debs".to_owned(), "git"),
+                ("pkg".to_owned(), "tarball"),
+            ]
+        );
+        assert_eq!(
+            kinds(&[DepCategory::Development]),
+            vec![("shared".to_owned(), "link")]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_yarn_classic_lockfile() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/yarn/");
+
+        let lock = parse_lockfile(root, &["./".to_owned(), "packages/web/".to_owned()])?;
+
+        let pkg_json = parse_package_json(&root.join("packages/web/"))?;
+        let root_pkg_json = parse_package_json(root)?;
+
+        let (prod, _) = prod_and_dev(get_deps_version(&pkg_json, "packages/web/", &lock));
+        let (_, root_dev) = prod_and_dev(get_deps_version(&root_pkg_json, "./", &lock));
+
+        assert_eq!(prod.len(), 2);
+        assert!(prod.contains(&PkgNameAndVersion("debug".to_owned(), "2.6.9".to_owned())));
+        assert_eq!(
+            root_dev,
+            vec![PkgNameAndVersion("debug".to_owned(), "4.3.4".to_owned())]
+        );
+
+        let transitive = get_transitive_deps(&pkg_json, "packages/web/", &lock, &prod);
+
+        assert_eq!(
+            transitive,
+            vec![
+                TransitivePkg(
+                    PkgNameAndVersion("ms".to_owned(), "2.0.0".to_owned()),
+                    vec!["debug".to_owned(), "express".to_owned()]
+                ),
+                TransitivePkg(
+                    PkgNameAndVersion("serve-static".to_owned(), "1.15.0".to_owned()),
+                    vec!["express".to_owned()]
+                ),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_yarn_berry_lockfile() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/yarn-berry/");
+
+        let lock = parse_lockfile(root, &["packages/web/".to_owned()])?;
+
+        let pkg_json = parse_package_json(&root.join("packages/web/"))?;
+
+        let (prod, _) = prod_and_dev(get_deps_version(&pkg_json, "packages/web/", &lock));
+
+        // `common` is a workspace
+        assert_eq!(
+            prod,
+            vec![PkgNameAndVersion("express".to_owned(), "4.18.2".to_owned())]
+        );
+
+        let paths = get_dependency_paths(
+            root,
+            "packages/web/",
+            &lock,
+            "ms",
+            None,
+            &[DepCategory::Production],
+        )?;
+
+        assert_eq!(
+            paths,
+            vec![
+                vec![
+                    PkgNameAndVersion("common".to_owned(), "".to_owned()),
+                    PkgNameAndVersion("ms".to_owned(), "2.1.3".to_owned()),
+                ],
+                vec![
+                    PkgNameAndVersion("express".to_owned(), "4.18.2".to_owned()),
+                    PkgNameAndVersion("debug".to_owned(), "2.6.9".to_owned()),
+                    PkgNameAndVersion("ms".to_owned(), "2.0.0".to_owned()),
+                ],
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_pnpm_lockfile() -> Result<(), DebsError> {
+        let root = Path::new("test-assets/pnpm/");
+
+        let lock = parse_lockfile(root, &["packages/web/".to_owned()])?;
+
+        let pkg_json = parse_package_json(&root.join("packages/web/"))?;
+
+        let (prod, dev) = prod_and_dev(get_deps_version(&pkg_json, "packages/web/", &lock));
+
+        // `common` is a workspace
+        assert_eq!(
+            prod,
+            vec![PkgNameAndVersion(
+                "react-dom".to_owned(),
+                "18.2.0".to_owned()
+            )]
+        );
+        assert_eq!(
+            dev,
+            vec![PkgNameAndVersion("express".to_owned(), "4.18.2".to_owned())]
+        );
+
+        let paths =
+            get_dependency_paths(root, "packages/web/", &lock, "ms", None, &DepCategory::ALL)?;
+
+        assert_eq!(
+            paths,
+            vec![
+                vec![
+                    PkgNameAndVersion("common".to_owned(), "".to_owned()),
+                    PkgNameAndVersion("ms".to_owned(), "2.1.3".to_owned()),
+                ],
+                vec![
+                    PkgNameAndVersion("express".to_owned(), "4.18.2".to_owned()),
+                    PkgNameAndVersion("debug".to_owned(), "2.6.9".to_owned()),
+                    PkgNameAndVersion("ms".to_owned(), "2.0.0".to_owned()),
+                ],
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_name_the_missing_file() {
+        let res = parse_package_lock(Path::new("missing-path/"));
+
+        assert!(matches!(
+            res,
+            Err(DebsError::Io { path, .. }) if path == Path::new("missing-path/package-lock.json")
+        ));
+    }
+
+    // UTILS
+
+    /// Util for easily testing the parsing of a monorepo style package-lock.json
+    ///
+    /// ## Arguments
+    ///
+    /// - **workspace**: path to the chosen workspace's `package.json`, relative to the monorepo root
+    fn parse_lock(workspace: &str) -> Vec<PkgNameAndVersion> {
+        let root = Path::new("test-assets/monorepo/");
+
+        let lock = parse_lockfile(root, &[workspace.to_owned()]);
+        let pkg_json = parse_package_json(&root.join(workspace));
+
+        assert!(lock.is_ok() && pkg_json.is_ok());
+
+        prod_and_dev(get_deps_version(
+            &pkg_json.unwrap(),
+            workspace,
+            &lock.unwrap(),
+        ))
+        .0
+    }
+
+    /// Production and development dependencies, out of every category
+    fn prod_and_dev<T>(mut deps: DepsByCategory<T>) -> (Vec<T>, Vec<T>) {
+        (
+            deps.remove(&DepCategory::Production).unwrap_or_default(),
+            deps.remove(&DepCategory::Development).unwrap_or_default(),
+        )
+    }
+}
@@ -0,0 +1,370 @@
+//! Reader for pnpm's lockfile, `pnpm-lock.yaml`, and its workspace configuration, `pnpm-workspace.yaml`.
+//!
+//! Each workspace (an importer) lists the version it installed for each of its dependencies:
+//!
+//! ``` yaml
+//! importers:
+//!   packages/web:
+//!     dependencies:
+//!       common:
+//!         specifier: workspace:*
+//!         version: link:../common
+//!       react-dom:
+//!         specifier: ^18.2.0
+//!         version: 18.2.0(react@18.2.0)
+//! ```
+//!
+//! Versions point to the entries of `packages` (`snapshots` since lockfile v9), keyed by `/<name>@<version>`
+//! (`<name>@<version>` since v9, `/<name>/<version>` before v6), which list their own dependencies the same way.
+//! Versions may end with the peer dependencies the package was installed with, e.g. `(react@18.2.0)`
+//! (`_react@18.2.0` before v6). Lockfiles older than v6 list versions directly, without `specifier`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use super::{workspace_key, yaml_string, Lockfile, PackageLockDepInfo};
+use crate::error::DebsError;
+
+/// Reads `pnpm-lock.yaml`, where each importer is keyed by the path of its workspace.
+pub fn parse_pnpm_lock(path: &Path) -> Result<Lockfile, DebsError> {
+    Ok(read_pnpm_lock(&parse_yaml(path)?))
+}
+
+/// Workspace paths and globs listed in the `packages` field of `pnpm-workspace.yaml`,
+/// or `None` when the project at `root` has no such file.
+pub fn pnpm_workspace_patterns(root: &Path) -> Result<Option<Vec<String>>, DebsError> {
+    let path = root.join("pnpm-workspace.yaml");
+
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let patterns = parse_yaml(&path)?
+        .get("packages")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(str::to_owned)
+        .collect();
+
+    Ok(Some(patterns))
+}
+
+fn parse_yaml(path: &Path) -> Result<Value, DebsError> {
+    let content = std::fs::read_to_string(path).map_err(|source| DebsError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    serde_yaml::from_str(&content).map_err(|e| DebsError::yaml_parse(path.to_owned(), e))
+}
+
+fn read_pnpm_lock(document: &Value) -> Lockfile {
+    // `5.4`, `'6.0'`, `'9.0'`, ...
+    let legacy = document
+        .get("lockfileVersion")
+        .and_then(yaml_string)
+        .and_then(|version| version.split('.').next()?.parse::<u32>().ok())
+        .is_some_and(|major| major < 6);
+
+    let mut packages = HashMap::new();
+
+    // lockfiles of projects without workspaces may list the root's dependencies at the top level
+    let importers: Vec<(String, &Value)> =
+        match document.get("importers").and_then(Value::as_mapping) {
+            Some(importers) => importers
+                .iter()
+                .filter_map(|(path, importer)| Some((workspace_key(&yaml_string(path)?), importer)))
+                .collect(),
+            None => vec![(String::new(), document)],
+        };
+
+    for (key, importer) in importers {
+        let deps = |field: &str| importer_deps(importer, field, &key);
+
+        let info = PackageLockDepInfo {
+            dependencies: deps("dependencies"),
+            optional_dependencies: deps("optionalDependencies"),
+            dev_dependencies: deps("devDependencies"),
+            ..Default::default()
+        };
+
+        packages.insert(key, info);
+    }
+
+    // since v9, `packages` only holds metadata, and `snapshots` the dependencies
+    let entries = document
+        .get("snapshots")
+        .or_else(|| document.get("packages"))
+        .and_then(Value::as_mapping);
+
+    for (key, entry) in entries.into_iter().flatten() {
+        let Some((name, reference)) = yaml_string(key).and_then(|key| split_key(&key, legacy))
+        else {
+            continue;
+        };
+
+        let deps = |field: &str| -> HashMap<String, String> {
+            entry
+                .get(field)
+                .and_then(Value::as_mapping)
+                .into_iter()
+                .flatten()
+                .filter_map(|(dep_name, reference)| {
+                    Some((yaml_string(dep_name)?, yaml_string(reference)?))
+                })
+                .collect()
+        };
+
+        let info = PackageLockDepInfo {
+            name: Some(name.clone()),
+            version: registry_version(&reference, legacy),
+            dependencies: deps("dependencies"),
+            optional_dependencies: deps("optionalDependencies"),
+            ..Default::default()
+        };
+
+        packages.insert(format!("{name}@{reference}"), info);
+    }
+
+    // pnpm records what each dependency resolved to, which we use as descriptors
+    let mut descriptors = HashMap::new();
+
+    for info in packages.values() {
+        for (dep_name, reference) in info
+            .dependencies
+            .iter()
+            .chain(&info.optional_dependencies)
+            .chain(&info.dev_dependencies)
+        {
+            let key = reference_key(dep_name, reference, legacy);
+
+            if packages.contains_key(&key) {
+                descriptors.insert(format!("{dep_name}@{reference}"), key);
+            }
+        }
+    }
+
+    Lockfile {
+        packages,
+        descriptors: Some(descriptors),
+    }
+}
+
+/// Dependencies of an importer, by name. Links to other workspaces point to their path from the root,
+/// e.g. `link:packages/common` rather than `link:../common`.
+fn importer_deps(importer: &Value, field: &str, importer_key: &str) -> HashMap<String, String> {
+    importer
+        .get(field)
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(dep_name, dep)| {
+            // `{ specifier, version }` since v6
+            let reference = yaml_string(dep.get("version").unwrap_or(dep))?;
+
+            let reference = match reference.strip_prefix("link:") {
+                Some(target) => format!("link:{}", join_path(importer_key, target)),
+                None => reference,
+            };
+
+            Some((yaml_string(dep_name)?, reference))
+        })
+        .collect()
+}
+
+/// `/@babel/core@7.22.5` -> `("@babel/core", "7.22.5")`, or `/@babel/core/7.22.5` before v6.
+fn split_key(key: &str, legacy: bool) -> Option<(String, String)> {
+    let key = key.strip_prefix('/').unwrap_or(key);
+
+    let separator = if legacy {
+        // the name of scoped packages has a `/` too
+        let skipped = if key.starts_with('@') {
+            key.find('/')? + 1
+        } else {
+            0
+        };
+
+        skipped + key[skipped..].find('/')?
+    } else {
+        // the `@` of scoped packages is not a separator
+        1 + key.get(1..)?.find('@')?
+    };
+
+    Some((key[..separator].to_owned(), key[separator + 1..].to_owned()))
+}
+
+/// Key of the package a dependency resolved to, from the version recorded for it.
+fn reference_key(dep_name: &str, reference: &str, legacy: bool) -> String {
+    if let Some(target) = reference.strip_prefix("link:") {
+        return target.to_owned();
+    }
+
+    // aliases and packages from outside the registry are written as keys, e.g. `/string-width@4.2.3`,
+    // or `string-width@4.2.3` since v9
+    let is_key = reference.starts_with('/')
+        || !reference.starts_with(|c: char| c.is_ascii_digit()) && reference.contains('@');
+
+    match split_key(reference, legacy) {
+        Some((name, version)) if is_key => format!("{name}@{version}"),
+        _ => format!("{dep_name}@{reference}"),
+    }
+}
+
+/// `18.2.0(react@18.2.0)` -> `18.2.0`, or `18.2.0_react@18.2.0` before v6.
+///
+/// `None` for packages which are not installed from the registry, whose version is not a semver version.
+fn registry_version(reference: &str, legacy: bool) -> Option<String> {
+    let peers_separator = if legacy { '_' } else { '(' };
+
+    let version = reference.split(peers_separator).next().unwrap_or_default();
+
+    version
+        .starts_with(|c: char| c.is_ascii_digit())
+        .then(|| version.to_owned())
+}
+
+/// Joins a path relative to the workspace at `base` into a path relative to the root,
+/// e.g. `packages/web` and `../common` -> `packages/common`.
+fn join_path(base: &str, relative: &str) -> String {
+    let base = workspace_key(base);
+
+    let mut segments: Vec<&str> = base
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    for segment in relative.split(['/', '\\']) {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::PkgNameAndVersion;
+
+    use super::*;
+
+    #[test]
+    fn should_split_package_keys() {
+        assert_eq!(
+            split_key("/@babel/core@7.22.5", false),
+            Some(("@babel/core".to_owned(), "7.22.5".to_owned()))
+        );
+        assert_eq!(
+            split_key("react-dom@18.2.0(react@18.2.0)", false),
+            Some(("react-dom".to_owned(), "18.2.0(react@18.2.0)".to_owned()))
+        );
+        assert_eq!(
+            split_key("/@babel/core/7.22.5_supports-color@5.5.0", true),
+            Some((
+                "@babel/core".to_owned(),
+                "7.22.5_supports-color@5.5.0".to_owned()
+            ))
+        );
+        assert_eq!(
+            registry_version("7.22.5_supports-color@5.5.0", true),
+            Some("7.22.5".to_owned())
+        );
+        assert_eq!(registry_version("link:../common", false), None);
+    }
+
+    #[test]
+    fn should_resolve_importer_links() {
+        assert_eq!(join_path("packages/web", "../common"), "packages/common");
+        assert_eq!(join_path(".", "./packages/common"), "packages/common");
+        assert_eq!(
+            reference_key("common", "link:packages/common", false),
+            "packages/common"
+        );
+        assert_eq!(
+            reference_key("string-width-cjs", "/string-width@4.2.3", false),
+            "string-width@4.2.3"
+        );
+        assert_eq!(reference_key("ms", "2.1.2", false), "ms@2.1.2");
+    }
+
+    #[test]
+    fn should_read_v6_lockfile() -> Result<(), serde_yaml::Error> {
+        let lock = read_pnpm_lock(&serde_yaml::from_str(
+            r#"lockfileVersion: '6.0'
+
+importers:
+
+  apps/web:
+    dependencies:
+      string-width-cjs:
+        specifier: npm:string-width@^4.2.0
+        version: /string-width@4.2.3
+
+packages:
+
+  /string-width@4.2.3:
+    resolution: {integrity: sha512-wKyQRQpjJ0sIp62ErSZdGsjMJWsap5oRNihHhu6G7JVO/9jIB6UyevL+tXuOqrng8j/cxKTWyWUwvSTriiZz/g==}
+    engines: {node: '>=8'}
+    dev: false
+"#,
+        )?);
+
+        let key = lock.installed("apps/web/", "string-width-cjs");
+
+        assert_eq!(key.as_deref(), Some("string-width@4.2.3"));
+        assert_eq!(
+            lock.registry_pkg("string-width@4.2.3"),
+            Some(PkgNameAndVersion(
+                "string-width".to_owned(),
+                "4.2.3".to_owned()
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_legacy_lockfile_without_importers() -> Result<(), serde_yaml::Error> {
+        let lock = read_pnpm_lock(&serde_yaml::from_str(
+            r#"lockfileVersion: 5.4
+
+specifiers:
+  debug: ^4.3.4
+
+dependencies:
+  debug: 4.3.4
+
+packages:
+
+  /debug/4.3.4:
+    resolution: {integrity: sha512-PRWFHuSU3eDtQJPvnNY7Jcket1j0t5OuOsFzPPzsekD52Zl8qUfFIPEiswXqIvHWGVHOgX+7G/vCNNhehwxfkQ==}
+    dependencies:
+      ms: 2.1.2
+    dev: false
+
+  /ms/2.1.2:
+    resolution: {integrity: sha512-sGkPx+VjMtmA6MX27oA4FBFELFCZZ4S4XqeVVy2IBo0JpdPTBtEnMwmmhRUiNfTKkBiSIF4umKOSPj1VBvafzQ==}
+    dev: false
+"#,
+        )?);
+
+        assert_eq!(
+            lock.installed("./", "debug"),
+            Some("debug@4.3.4".to_owned())
+        );
+        assert_eq!(
+            lock.installed("debug@4.3.4", "ms"),
+            Some("ms@2.1.2".to_owned())
+        );
+
+        Ok(())
+    }
+}
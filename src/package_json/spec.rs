@@ -0,0 +1,206 @@
+//! Reader for the dependency specs of package.json, i.e. what each dependency name maps to.
+//!
+//! Besides version ranges and tags, which are resolved by the registry, npm accepts (see [package.json]):
+//!
+//! ``` json
+//! "dependencies": {
+//!     "foo": "npm:bar@^2",
+//!     "debs": "git+ssh://git@github.com/eloyrobillard/debs.git#v1.0.0",
+//!     "common": "file:../common",
+//!     "shared": "link:../shared",
+//!     "pkg": "https://example.com/pkg-1.0.0.tgz"
+//! }
+//! ```
+//!
+//! Aliases (`npm:`) are installed from the registry under another name, while the others are not
+//! installed from the registry at all. Yarn and pnpm also use `workspace:` to depend on other workspaces.
+//!
+//! [package.json]: https://docs.npmjs.com/cli/v9/configuring-npm/package-json#dependencies
+
+use std::fmt;
+
+use crate::types::PkgName;
+
+/// What a dependency name of package.json maps to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DepSpec {
+    /// Range (`^1.2.3`) or tag (`latest`), resolved by the registry.
+    Registry(String),
+    /// `npm:<name>@<range>`: a registry package installed under another name.
+    Alias { name: PkgName, range: String },
+    /// `git+ssh://...`, `github:user/repo`, `user/repo#ref`, ...
+    Git(String),
+    /// `file:../common`, or a path such as `./common` or `vendor/pkg.tgz`.
+    File(String),
+    /// `link:../shared` (Yarn and pnpm).
+    Link(String),
+    /// `https://example.com/pkg-1.0.0.tgz`
+    Tarball(String),
+    /// `workspace:^` (Yarn and pnpm). Workspaces are checked on their own.
+    Workspace(String),
+}
+
+/// Prefixes of git specs, besides `git+<protocol>://`.
+static GIT_PREFIXES: [&str; 5] = ["git://", "github:", "gitlab:", "bitbucket:", "gist:"];
+
+impl DepSpec {
+    /// Classifies a spec the way npm does, see the [module documentation](self).
+    pub fn parse(spec: &str) -> DepSpec {
+        let spec = spec.trim();
+        let raw = spec.to_owned();
+
+        if let Some(aliased) = spec.strip_prefix("npm:") {
+            // the `@` of scoped packages is not a version separator
+            let (name, range) = match aliased.rfind('@') {
+                Some(i) if i > 0 => (&aliased[..i], &aliased[i + 1..]),
+                _ => (aliased, "*"),
+            };
+
+            return DepSpec::Alias {
+                name: name.to_owned(),
+                range: range.to_owned(),
+            };
+        }
+
+        if spec.starts_with("workspace:") {
+            DepSpec::Workspace(raw)
+        } else if spec.starts_with("link:") {
+            DepSpec::Link(raw)
+        } else if spec.starts_with("file:") {
+            DepSpec::File(raw)
+        } else if spec.starts_with("git+") || GIT_PREFIXES.iter().any(|p| spec.starts_with(p)) {
+            DepSpec::Git(raw)
+        } else if spec.starts_with("http://") || spec.starts_with("https://") {
+            let url = spec.split('#').next().unwrap_or_default();
+
+            if url.ends_with(".git") {
+                DepSpec::Git(raw)
+            } else {
+                DepSpec::Tarball(raw)
+            }
+        } else if is_path(spec) {
+            DepSpec::File(raw)
+        } else if is_github_shorthand(spec) {
+            DepSpec::Git(raw)
+        } else {
+            DepSpec::Registry(raw)
+        }
+    }
+
+    /// Whether the dependency is installed from the registry, under its own name or an alias.
+    pub fn is_registry(&self) -> bool {
+        matches!(self, DepSpec::Registry(_) | DepSpec::Alias { .. })
+    }
+
+    /// Name of the kind of spec, e.g. `git`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DepSpec::Registry(_) => "registry",
+            DepSpec::Alias { .. } => "alias",
+            DepSpec::Git(_) => "git",
+            DepSpec::File(_) => "file",
+            DepSpec::Link(_) => "link",
+            DepSpec::Tarball(_) => "tarball",
+            DepSpec::Workspace(_) => "workspace",
+        }
+    }
+}
+
+impl fmt::Display for DepSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepSpec::Alias { name, range } => write!(f, "npm:{name}@{range}"),
+            DepSpec::Registry(spec)
+            | DepSpec::Git(spec)
+            | DepSpec::File(spec)
+            | DepSpec::Link(spec)
+            | DepSpec::Tarball(spec)
+            | DepSpec::Workspace(spec) => write!(f, "{spec}"),
+        }
+    }
+}
+
+/// Dependency which is not installed from the registry (e.g. from git or a local folder), along with its spec.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonRegistryPkg(pub PkgName, pub DepSpec);
+
+/// `./common`, `../common`, `/opt/common`, `~/common`, or a local tarball such as `vendor/pkg.tgz`.
+fn is_path(spec: &str) -> bool {
+    spec == "."
+        || spec == ".."
+        || ["./", "../", "/", "~/"]
+            .iter()
+            .any(|prefix| spec.starts_with(prefix))
+        || [".tgz", ".tar.gz", ".tar"]
+            .iter()
+            .any(|extension| spec.ends_with(extension))
+}
+
+/// `user/repo` or `user/repo#ref`, which npm fetches from GitHub. Ranges never contain a `/`.
+fn is_github_shorthand(spec: &str) -> bool {
+    let repo = spec.split('#').next().unwrap_or_default();
+
+    match repo.split_once('/') {
+        Some((user, repo)) => {
+            !user.is_empty()
+                && !repo.is_empty()
+                && !repo.contains('/')
+                && !user.starts_with('@')
+                && !spec.contains(':')
+                && !spec.contains(' ')
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_registry_specs_and_aliases() {
+        assert_eq!(
+            DepSpec::parse("^1.2.3"),
+            DepSpec::Registry("^1.2.3".to_owned())
+        );
+        assert_eq!(
+            DepSpec::parse(">=1.0.0 <2"),
+            DepSpec::Registry(">=1.0.0 <2".to_owned())
+        );
+        assert_eq!(
+            DepSpec::parse("latest"),
+            DepSpec::Registry("latest".to_owned())
+        );
+        assert_eq!(
+            DepSpec::parse("npm:bar@^2"),
+            DepSpec::Alias {
+                name: "bar".to_owned(),
+                range: "^2".to_owned()
+            }
+        );
+        assert_eq!(
+            DepSpec::parse("npm:@scope/bar"),
+            DepSpec::Alias {
+                name: "@scope/bar".to_owned(),
+                range: "*".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn should_classify_non_registry_specs() {
+        let kind = |spec: &str| DepSpec::parse(spec).kind();
+
+        assert_eq!(kind("git+ssh://git@github.com/user/debs.git"), "git");
+        assert_eq!(kind("git://github.com/user/debs.git#v1.0.0"), "git");
+        assert_eq!(kind("github:user/debs"), "git");
+        assert_eq!(kind("user/debs#main"), "git");
+        assert_eq!(kind("https://github.com/user/debs.git"), "git");
+        assert_eq!(kind("https://example.com/pkg-1.0.0.tgz"), "tarball");
+        assert_eq!(kind("file:../common"), "file");
+        assert_eq!(kind("../common"), "file");
+        assert_eq!(kind("vendor/pkg-1.0.0.tgz"), "file");
+        assert_eq!(kind("link:../shared"), "link");
+        assert_eq!(kind("workspace:^"), "workspace");
+    }
+}
@@ -0,0 +1,329 @@
+//! Readers for Yarn's lockfile, `yarn.lock`, which comes in two formats.
+//!
+//! Yarn classic (v1) uses its own format, close to YAML:
+//!
+//! ``` text
+//! "@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+//!   version "7.12.13"
+//!   resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz"
+//!   dependencies:
+//!     "@babel/highlight" "^7.12.13"
+//! ```
+//!
+//! Yarn Berry (v2 and later) writes actual YAML, and records workspaces as well:
+//!
+//! ``` yaml
+//! "debug@npm:^4.1.0, debug@npm:^4.3.4":
+//!   version: 4.3.4
+//!   resolution: "debug@npm:4.3.4"
+//!   dependencies:
+//!     ms: 2.1.2
+//! ```
+//!
+//! In both cases, each entry starts with the descriptors (`<name>@<range>`) resolving to the package.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_yaml::Value;
+
+use super::{workspace_key, yaml_string, Lockfile, PackageLockDepInfo};
+use crate::error::DebsError;
+
+/// Reads `yarn.lock`, telling Yarn Berry lockfiles apart by their `__metadata` entry.
+pub fn parse_yarn_lock(path: &Path) -> Result<Lockfile, DebsError> {
+    let content = std::fs::read_to_string(path).map_err(|source| DebsError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    if content.lines().any(|line| line.starts_with("__metadata:")) {
+        parse_berry(&content).map_err(|e| DebsError::yaml_parse(path.to_owned(), e))
+    } else {
+        Ok(parse_classic(&content))
+    }
+}
+
+/// Key of the package `<pkg_name>@<range>` resolves to, if it is installed.
+///
+/// Yarn Berry adds the default `npm:` protocol to the descriptors of the lockfile, but not to dependency ranges,
+/// and workspaces are required with ranges such as `workspace:^`, which are not recorded.
+pub fn resolve_descriptor(
+    descriptors: &HashMap<String, String>,
+    pkg_name: &str,
+    range: &str,
+) -> Option<String> {
+    if range.starts_with("workspace:") {
+        let prefix = format!("{pkg_name}@workspace:");
+
+        return descriptors
+            .iter()
+            .find(|(descriptor, _)| descriptor.starts_with(&prefix))
+            .map(|(_, key)| key.clone());
+    }
+
+    descriptors
+        .get(&format!("{pkg_name}@{range}"))
+        .or_else(|| descriptors.get(&format!("{pkg_name}@npm:{range}")))
+        .cloned()
+}
+
+fn parse_classic(content: &str) -> Lockfile {
+    let mut lock = Lockfile {
+        packages: HashMap::new(),
+        descriptors: Some(HashMap::new()),
+    };
+
+    // descriptors and fields of the entry being read, and the dependencies section being read
+    let mut entry: Option<(Vec<String>, PackageLockDepInfo)> = None;
+    let mut section: Option<String> = None;
+
+    for line in content.lines() {
+        let content = line.trim();
+
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        match line.len() - line.trim_start().len() {
+            0 => {
+                if let Some((descriptors, info)) = entry.take() {
+                    add_classic_entry(&mut lock, descriptors, info);
+                }
+
+                let descriptors = content
+                    .trim_end_matches(':')
+                    .split(", ")
+                    .map(unquote)
+                    .collect();
+
+                entry = Some((descriptors, PackageLockDepInfo::default()));
+                section = None;
+            }
+            2 => {
+                section = content.strip_suffix(':').map(str::to_owned);
+
+                if let (Some((_, info)), Some(("version", version))) =
+                    (&mut entry, split_pair(content))
+                {
+                    info.version = Some(version.to_owned());
+                }
+            }
+            _ => {
+                let (Some((_, info)), Some((dep_name, range))) = (&mut entry, split_pair(content))
+                else {
+                    continue;
+                };
+
+                let deps = match section.as_deref() {
+                    Some("dependencies") => &mut info.dependencies,
+                    Some("optionalDependencies") => &mut info.optional_dependencies,
+                    Some("peerDependencies") => &mut info.peer_dependencies,
+                    _ => continue,
+                };
+
+                deps.insert(dep_name.to_owned(), range.to_owned());
+            }
+        }
+    }
+
+    if let Some((descriptors, info)) = entry {
+        add_classic_entry(&mut lock, descriptors, info);
+    }
+
+    lock
+}
+
+fn add_classic_entry(lock: &mut Lockfile, descriptors: Vec<String>, mut info: PackageLockDepInfo) {
+    let (Some(name), Some(version)) = (
+        descriptors
+            .first()
+            .map(|descriptor| descriptor_name(descriptor)),
+        &info.version,
+    ) else {
+        return;
+    };
+
+    let key = format!("{name}@{version}");
+
+    info.name = Some(name.to_owned());
+
+    lock.descriptors.get_or_insert_with(HashMap::new).extend(
+        descriptors
+            .into_iter()
+            .map(|descriptor| (descriptor, key.clone())),
+    );
+
+    lock.packages.insert(key, info);
+}
+
+fn parse_berry(content: &str) -> Result<Lockfile, serde_yaml::Error> {
+    let entries: HashMap<String, Value> = serde_yaml::from_str(content)?;
+
+    let mut lock = Lockfile {
+        packages: HashMap::new(),
+        descriptors: Some(HashMap::new()),
+    };
+
+    for (descriptors, entry) in entries {
+        if descriptors == "__metadata" {
+            continue;
+        }
+
+        let Some(resolution) = entry.get("resolution").and_then(yaml_string) else {
+            continue;
+        };
+
+        let name = descriptor_name(&resolution).to_owned();
+        let protocol = resolution.get(name.len() + 1..).unwrap_or_default();
+
+        let (key, version) = if let Some(workspace) = protocol.strip_prefix("workspace:") {
+            (workspace_key(workspace), None)
+        } else if let Some(version) = protocol.strip_prefix("npm:") {
+            (format!("{name}@{version}"), Some(version.to_owned()))
+        } else {
+            // patches, git repositories, linked folders, ... are not installed from the registry
+            (resolution.clone(), None)
+        };
+
+        let deps = |field: &str| -> HashMap<String, String> {
+            entry
+                .get(field)
+                .and_then(Value::as_mapping)
+                .into_iter()
+                .flatten()
+                .filter_map(|(dep_name, range)| Some((yaml_string(dep_name)?, yaml_string(range)?)))
+                .collect()
+        };
+
+        let info = PackageLockDepInfo {
+            name: Some(name),
+            version,
+            dependencies: deps("dependencies"),
+            peer_dependencies: deps("peerDependencies"),
+            ..Default::default()
+        };
+
+        lock.descriptors.get_or_insert_with(HashMap::new).extend(
+            descriptors
+                .split(", ")
+                .map(|descriptor| (unquote(descriptor), key.clone())),
+        );
+
+        lock.packages.insert(key, info);
+    }
+
+    Ok(lock)
+}
+
+/// `@babel/core@^7.0.0` -> `@babel/core`, `alias@npm:react@^18` -> `alias`
+fn descriptor_name(descriptor: &str) -> &str {
+    // the `@` of scoped packages is not a separator
+    match descriptor.get(1..).and_then(|rest| rest.find('@')) {
+        Some(i) => &descriptor[..i + 1],
+        None => descriptor,
+    }
+}
+
+/// `"@babel/highlight" "^7.12.13"` -> `("@babel/highlight", "^7.12.13")`
+fn split_pair(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = match line.strip_prefix('"') {
+        Some(rest) => rest.split_once('"')?,
+        None => line.split_once(' ')?,
+    };
+
+    Some((key, value.trim().trim_matches('"')))
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_yarn_classic_lockfile() {
+        let lock = parse_classic(
+            r#"# THIS IS AN AUTOGENERATED FILE. DO NOT EDIT THIS FILE DIRECTLY.
+# yarn lockfile v1
+
+
+"@babel/code-frame@^7.0.0", "@babel/code-frame@^7.10.4":
+  version "7.12.13"
+  resolved "https://registry.yarnpkg.com/@babel/code-frame/-/code-frame-7.12.13.tgz"
+  dependencies:
+    "@babel/highlight" "^7.12.13"
+
+chokidar@^3.5.3:
+  version "3.5.3"
+  optionalDependencies:
+    fsevents "~2.3.2"
+"#,
+        );
+
+        let descriptors = lock.descriptors.as_ref().expect("Yarn lockfile");
+
+        assert_eq!(
+            resolve_descriptor(descriptors, "@babel/code-frame", "^7.10.4"),
+            Some("@babel/code-frame@7.12.13".to_owned())
+        );
+        assert_eq!(
+            lock.packages["@babel/code-frame@7.12.13"].dependencies,
+            HashMap::from([("@babel/highlight".to_owned(), "^7.12.13".to_owned())])
+        );
+        assert_eq!(
+            lock.packages["chokidar@3.5.3"].optional_dependencies,
+            HashMap::from([("fsevents".to_owned(), "~2.3.2".to_owned())])
+        );
+    }
+
+    #[test]
+    fn should_parse_yarn_berry_lockfile() -> Result<(), serde_yaml::Error> {
+        let lock = parse_berry(
+            r#"__metadata:
+  version: 6
+  cacheKey: 8
+
+"common@workspace:^, common@workspace:common":
+  version: 0.0.0-use.local
+  resolution: "common@workspace:common"
+  dependencies:
+    ms: 2
+  languageName: unknown
+  linkType: soft
+
+"ms@npm:2, ms@npm:2.1.2":
+  version: 2.1.2
+  resolution: "ms@npm:2.1.2"
+  checksum: 673cdb2c31
+  languageName: node
+  linkType: hard
+"#,
+        )?;
+
+        let descriptors = lock.descriptors.as_ref().expect("Yarn lockfile");
+
+        assert_eq!(
+            resolve_descriptor(descriptors, "ms", "2"),
+            Some("ms@2.1.2".to_owned())
+        );
+        assert_eq!(
+            resolve_descriptor(descriptors, "common", "workspace:*"),
+            Some("common".to_owned())
+        );
+        // workspaces are not installed from the registry
+        assert_eq!(lock.packages["common"].version, None);
+        assert_eq!(lock.packages["ms@2.1.2"].version.as_deref(), Some("2.1.2"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_split_descriptor_names() {
+        assert_eq!(descriptor_name("lodash@^4.17.21"), "lodash");
+        assert_eq!(descriptor_name("@babel/core@npm:^7.0.0"), "@babel/core");
+        assert_eq!(descriptor_name("alias@npm:react@^18"), "alias");
+    }
+}
@@ -0,0 +1,187 @@
+//! On-disk cache for registry responses.
+//!
+//! Each response is stored as a JSON file named after the request URL, together with
+//! the `ETag` returned by the registry and the time at which it was fetched:
+//!
+//! ``` json
+//! {
+//!     "etag": "W/\"9f2c...\"",
+//!     "fetched_at": 1686772000,
+//!     "body": { "name": "react", "dist-tags": { ... }, "time": { ... } }
+//! }
+//! ```
+//!
+//! Only the fields debs deserializes are kept in `body`, which keeps entries small
+//! even for packages with multi-megabyte metadata documents.
+//!
+//! Entries younger than the TTL are used as-is. Older ones are revalidated with `If-None-Match`,
+//! so that unchanged documents come back as `304 Not Modified` without a body.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Used to give concurrent writes to the same entry distinct temporary files.
+static TMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cache {
+    dir: PathBuf,
+    // in seconds
+    ttl: i64,
+    // ignore the TTL and revalidate every entry
+    refresh: bool,
+}
+
+/// A cached response, as stored on disk.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct CacheEntry<T> {
+    pub etag: Option<String>,
+    // UNIX timestamp, in seconds
+    pub fetched_at: i64,
+    pub body: T,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf, ttl_hours: u64, refresh: bool) -> Self {
+        Cache {
+            dir,
+            ttl: i64::try_from(ttl_hours.saturating_mul(3600)).unwrap_or(i64::MAX),
+            refresh,
+        }
+    }
+
+    /// `$XDG_CACHE_HOME/debs`, falling back to `~/.cache/debs` (or `%LOCALAPPDATA%\debs` on Windows).
+    pub fn default_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .map(|dir| dir.join("debs"))
+    }
+
+    /// Reads the entry for `url`, regardless of its age.
+    pub fn load<T: DeserializeOwned>(&self, url: &str) -> Option<CacheEntry<T>> {
+        let content = std::fs::read_to_string(self.entry_path(url)).ok()?;
+
+        // a corrupted or outdated entry is simply treated as missing
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Whether `entry` can be used without revalidating it with the registry.
+    pub fn is_fresh<T>(&self, entry: &CacheEntry<T>) -> bool {
+        !self.refresh && Utc::now().timestamp() - entry.fetched_at < self.ttl
+    }
+
+    /// Writes the entry for `url`, replacing any previous one.
+    pub fn store<T: Serialize>(&self, url: &str, entry: &CacheEntry<T>) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+
+        let path = self.entry_path(url);
+
+        // write to a temporary file first, so that readers never see a half-written entry
+        let tmp_path = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        std::fs::write(&tmp_path, serde_json::to_vec(entry)?)?;
+        std::fs::rename(&tmp_path, &path)
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", entry_name(url)))
+    }
+}
+
+/// Turns a URL into a file name, e.g. `https://registry.npmjs.org/@babel%2fcore` -> `registry.npmjs.org_2f@babel_252fcore`.
+///
+/// Characters other than ASCII alphanumerics, `.`, `-` and `@` are hex-encoded behind a `_`,
+/// so distinct URLs never share an entry.
+fn entry_name(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+
+    url.bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' | b'@' => (b as char).to_string(),
+            _ => format!("_{b:02x}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_names_should_be_distinct_file_names() {
+        assert_eq!(
+            entry_name("https://registry.npmjs.org/@babel%2fcore"),
+            "registry.npmjs.org_2f@babel_252fcore"
+        );
+        assert_ne!(
+            entry_name("https://registry.npmjs.org/a_b"),
+            entry_name("https://registry.npmjs.org/a/b")
+        );
+    }
+
+    #[test]
+    fn should_store_and_load_entries() -> std::io::Result<()> {
+        let dir = TestDir::new("store_and_load");
+        let cache = Cache::new(dir.0.clone(), 24, false);
+
+        let entry = CacheEntry {
+            etag: Some("\"abc\"".to_owned()),
+            fetched_at: Utc::now().timestamp(),
+            body: vec!["a".to_owned(), "b".to_owned()],
+        };
+
+        cache.store("https://registry.npmjs.org/a", &entry)?;
+
+        assert_eq!(cache.load("https://registry.npmjs.org/a"), Some(entry));
+        assert_eq!(
+            cache.load::<Vec<String>>("https://registry.npmjs.org/b"),
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_expire_entries_after_ttl() {
+        let entry = CacheEntry {
+            etag: None,
+            fetched_at: Utc::now().timestamp() - 2 * 3600,
+            body: (),
+        };
+
+        let dir = TestDir::new("ttl");
+
+        assert!(Cache::new(dir.0.clone(), 3, false).is_fresh(&entry));
+        assert!(!Cache::new(dir.0.clone(), 1, false).is_fresh(&entry));
+        // `--refresh` always revalidates
+        assert!(!Cache::new(dir.0.clone(), 3, true).is_fresh(&entry));
+    }
+
+    // UTILS
+
+    /// Directory in the system temp dir, removed along with its entries when dropped.
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            TestDir(std::env::temp_dir().join(format!("debs-tests-{}-{name}", std::process::id())))
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            // the directory is only created once an entry is stored
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+}
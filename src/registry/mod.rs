@@ -1,15 +1,247 @@
 use std::collections::HashMap;
-use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
+mod cache;
+mod npmrc;
+mod retry;
 #[cfg(test)]
 mod tests;
 
-use serde::Deserialize;
+use chrono::Utc;
+use reqwest::header::{ACCEPT, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::error::DebsError;
+pub use cache::Cache;
+use cache::CacheEntry;
+
+/// Registry used when none is configured through the CLI, the environment or `.npmrc`.
+pub static DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// `Accept` header value for full package metadata documents.
+static FULL_METADATA: &str = "application/json";
+
+/// `Accept` header value for [abbreviated metadata] documents (a.k.a. "corgi" documents).
+///
+/// [abbreviated metadata]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md#abbreviated-metadata-format
+static ABBREVIATED_METADATA: &str = "application/vnd.npm.install-v1+json";
+
+/// Default maximum number of requests sent to the registry at the same time.
+pub static DEFAULT_CONCURRENCY: usize = 16;
+
+/// Default amount of **seconds** after which a request to the registry is abandoned (and possibly retried).
+pub static DEFAULT_TIMEOUT_SECS: u64 = 30;
+
+/// Environment variable overriding the registry from `.npmrc` (but not `--registry`).
+pub static REGISTRY_ENV_VAR: &str = "DEBS_REGISTRY";
+
+/// Location of the npm registry (or mirror, e.g. Verdaccio or Artifactory) to query for package data.
+///
+/// Scoped packages (`@scope/name`) can live on a registry of their own, and any registry
+/// can require a bearer token. Both are configured through `.npmrc`, see [`Registry::resolve`].
+///
+/// Clones share the same HTTP client and concurrency limit.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    // always ends with a `/`, so that package names can be appended directly
+    url: String,
+    // `@scope` -> registry URL (also ending with a `/`)
+    scopes: HashMap<String, String>,
+    // "nerf-darted" registry URL (e.g. `//npm.example.com/path/`) -> auth token
+    auth_tokens: HashMap<String, String>,
+    // `None` when caching is disabled with `--no-cache`
+    cache: Option<Cache>,
+    // answer from the cache only, without ever contacting the registry
+    offline: bool,
+    // shared by all requests, to reuse connections
+    client: reqwest::Client,
+    // limits the number of requests in flight
+    permits: Arc<Semaphore>,
+}
+
+impl Registry {
+    pub fn new(url: &str) -> Self {
+        Registry {
+            url: with_trailing_slash(url),
+            scopes: HashMap::new(),
+            auth_tokens: HashMap::new(),
+            cache: None,
+            offline: false,
+            client: build_client(Duration::from_secs(DEFAULT_TIMEOUT_SECS)),
+            permits: Arc::new(Semaphore::new(DEFAULT_CONCURRENCY)),
+        }
+    }
+
+    /// Sets the maximum number of requests sent to the registry at the same time.
+    ///
+    /// With 0, requests would wait forever for a permit: `--concurrency` is checked by clap to be at least 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.permits = Arc::new(Semaphore::new(concurrency));
+        self
+    }
+
+    /// Sets the amount of **seconds** after which a request is abandoned (and possibly retried).
+    pub fn with_timeout(mut self, timeout_secs: u64) -> Self {
+        self.client = build_client(Duration::from_secs(timeout_secs));
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// In offline mode, every response comes from the cache, whatever its age.
+    /// Packages missing from the cache fail with [`DebsError::NotInCache`].
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Builds the registry settings from the user's and project's `.npmrc` files.
+    ///
+    /// The project `.npmrc` overrides the user one (`$NPM_CONFIG_USERCONFIG` or `~/.npmrc`).
+    /// The default registry URL is picked from the first source that defines one, in order:
+    ///
+    /// 1. the `--registry` CLI option
+    /// 2. the `DEBS_REGISTRY` environment variable
+    /// 3. the `registry=` line of the `.npmrc` files
+    /// 4. [`DEFAULT_REGISTRY`]
+    ///
+    /// `@scope:registry=<URL>` and `//<host>/<path>/:_authToken=<TOKEN>` lines are always read from `.npmrc`.
+    pub fn resolve(cli_registry: Option<&str>, project_path: &Path) -> Self {
+        let mut config = npmrc::user_npmrc_path()
+            .and_then(|path| npmrc::parse_npmrc(&path).ok())
+            .unwrap_or_default();
+
+        if let Ok(project_config) = npmrc::parse_npmrc(&project_path.join(".npmrc")) {
+            config.extend(project_config);
+        }
+
+        Registry::from_config(cli_registry, config)
+    }
+
+    fn from_config(cli_registry: Option<&str>, mut config: HashMap<String, String>) -> Self {
+        let from_env = std::env::var(REGISTRY_ENV_VAR)
+            .ok()
+            .filter(|url| !url.trim().is_empty());
+
+        let url = cli_registry
+            .map(str::to_owned)
+            .or(from_env)
+            .or_else(|| config.remove("registry"))
+            .unwrap_or_else(|| DEFAULT_REGISTRY.to_owned());
+
+        let mut registry = Registry::new(&url);
+
+        for (key, value) in config {
+            if let Some(scope) = key.strip_suffix(":registry") {
+                if scope.starts_with('@') {
+                    registry
+                        .scopes
+                        .insert(scope.to_owned(), with_trailing_slash(&value));
+                }
+            } else if let Some(nerf_dart) = key.strip_suffix(":_authToken") {
+                if nerf_dart.starts_with("//") {
+                    registry
+                        .auth_tokens
+                        .insert(with_trailing_slash(nerf_dart), value);
+                }
+            }
+        }
+
+        registry
+    }
+
+    /// Registry URL serving `pkg_name`, taking `@scope:registry` settings into account.
+    ///
+    /// Unscoped packages, and scopes without a registry of their own, use the default registry.
+    pub fn url_for(&self, pkg_name: &str) -> &str {
+        pkg_name
+            .split_once('/')
+            .filter(|(scope, _)| scope.starts_with('@'))
+            .and_then(|(scope, _)| self.scopes.get(scope))
+            .unwrap_or(&self.url)
+    }
+
+    /// Auth token to send to the registry at `url`, if any.
+    ///
+    /// Like npm, tokens are matched against the "nerf-darted" URL (scheme removed),
+    /// dropping one path segment at a time until a token is found or only the host remains.
+    fn auth_token_for(&self, url: &str) -> Option<&str> {
+        let mut nerf_dart = match url.split_once("//") {
+            Some((_, rest)) => format!("//{}", with_trailing_slash(rest)),
+            None => return None,
+        };
+
+        loop {
+            if let Some(token) = self.auth_tokens.get(&nerf_dart) {
+                return Some(token);
+            }
+
+            // drop the last path segment: `//host/a/b/` -> `//host/a/`
+            let trimmed = &nerf_dart[..nerf_dart.len() - 1];
+
+            match trimmed.rfind('/') {
+                Some(i) if i > 1 => nerf_dart.truncate(i + 1),
+                _ => return None,
+            }
+        }
+    }
+
+    /// URL of `<registry>/<pkg_name>`.
+    fn request_url(&self, pkg_name: &str) -> String {
+        // scoped packages must be requested as `@scope%2fname` (private registries reject `@scope/name`)
+        format!("{}{}", self.url_for(pkg_name), pkg_name.replace('/', "%2f"))
+    }
+
+    /// Prepares a `GET <registry>/<pkg_name>` request for the `accept` document format,
+    /// authenticated when a token is configured.
+    fn get(&self, pkg_name: &str, accept: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .get(self.request_url(pkg_name))
+            .header(ACCEPT, accept);
+
+        match self.auth_token_for(self.url_for(pkg_name)) {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::new(DEFAULT_REGISTRY)
+    }
+}
+
+fn build_client(timeout: Duration) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        // same failure case as `reqwest::Client::new()`, which panics as well
+        .expect("TLS backend cannot be initialized")
+}
+
+fn with_trailing_slash(url: &str) -> String {
+    let url = url.trim();
+
+    if url.ends_with('/') {
+        url.to_owned()
+    } else {
+        format!("{url}/")
+    }
+}
 
 /// Type corresponding to the response from a `GET https://registry.npmjs.org/:package` request to the [npm registry].
 ///
 /// [npm registry]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md
-#[derive(Debug, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
 pub struct PackageMetadata {
     pub name: String,
     // contains the version number for the latest version
@@ -22,17 +254,32 @@ pub struct PackageMetadata {
 /// We use this enum to parse `deprecated` field wether string or bool
 ///
 /// See [this thread](https://users.rust-lang.org/t/how-to-use-multiple-types-for-a-field-in-serde-json/36714/3).
-#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Clone, Deserialize, Serialize, Debug, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum DeprecatedField {
     String(String),
-    Bool(bool)
+    Bool(bool),
+}
+
+/// Type corresponding to the response from a `GET https://registry.npmjs.org/:package` request
+/// with the `Accept: application/vnd.npm.install-v1+json` header, a.k.a. the [abbreviated metadata] format.
+///
+/// This document is much lighter than [`PackageMetadata`], yet describes every version of the package.
+///
+/// [abbreviated metadata]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md#abbreviated-metadata-format
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct AbbreviatedMetadata {
+    pub name: String,
+    // version number -> version object. Used in `deprecated`
+    pub versions: HashMap<String, VersionObject>,
 }
 
-/// JSON Object returned by a `GET https://registry.npmjs.org/:package/:version` request to the [npm registry].
+/// Version object found in the `versions` field of [`AbbreviatedMetadata`] (only the fields used by debs).
+///
+/// It is identical to the JSON object returned by a `GET https://registry.npmjs.org/:package/:version` request to the [npm registry].
 ///
 /// [npm registry]: https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md
-#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 pub struct VersionObject {
     pub name: String,
     pub version: String,
@@ -41,29 +288,119 @@ pub struct VersionObject {
     pub deprecated: Option<DeprecatedField>,
 }
 
-/// `GET https://registry.npmjs.org/:package`
-pub async fn pkg_info(pkg_name: &str) -> Result<PackageMetadata, Box<dyn Error>> {
-    let resp = reqwest::get(format!("https://registry.npmjs.org/{pkg_name}")).await?;
+/// `GET <registry>/:package`
+pub async fn pkg_info(registry: &Registry, pkg_name: &str) -> Result<PackageMetadata, DebsError> {
+    fetch(registry, pkg_name, FULL_METADATA).await
+}
 
-    match serde_json::from_str(resp.text().await?.as_str()) {
-        Ok(json) => Ok(json),
-        Err(e) => Err(Box::<dyn Error>::from(format!("{pkg_name}: {e}"))),
-    }
+/// `GET <registry>/:package` with `Accept: application/vnd.npm.install-v1+json`
+///
+/// Registries that don't support the abbreviated format answer with the full document,
+/// which contains the same `versions` field.
+pub async fn pkg_abbreviated_info(
+    registry: &Registry,
+    pkg_name: &str,
+) -> Result<AbbreviatedMetadata, DebsError> {
+    fetch(registry, pkg_name, ABBREVIATED_METADATA).await
 }
 
-/// `GET https://registry.npmjs.org/:package/:version`
-pub async fn pkg_version_info(
+/// Sends `GET <registry>/<pkg_name>` for the `accept` document format through the cache, if enabled.
+///
+/// Fresh cache entries are returned without contacting the registry, while stale ones
+/// are revalidated with their `ETag` and reused when the registry answers `304 Not Modified`.
+/// In offline mode, cache entries are returned whatever their age.
+///
+/// Requests are retried on rate limiting and server errors, see [`retry`].
+async fn fetch<T: DeserializeOwned + Serialize>(
+    registry: &Registry,
     pkg_name: &str,
-    version: &str,
-) -> Result<VersionObject, Box<dyn Error>> {
-    let resp = reqwest::Client::new()
-        .get(format!("https://registry.npmjs.org/{pkg_name}/{version}"))
-        .send()
-        .await?;
+    accept: &str,
+) -> Result<T, DebsError> {
+    // both formats share the same URL, so the cache tells them apart with the `Accept` header
+    let url = format!("{} {accept}", registry.request_url(pkg_name));
 
-    match serde_json::from_str(resp.text().await?.as_str()) {
-        Ok(json) => Ok(json),
-        Err(e) => Err(Box::<dyn Error>::from(format!("{pkg_name}: {e}"))),
+    let cached = registry
+        .cache
+        .as_ref()
+        .and_then(|cache| cache.load::<T>(&url).map(|entry| (cache, entry)));
+
+    let mut request = registry.get(pkg_name, accept);
+
+    let cached = match cached {
+        Some((_, entry)) if registry.offline => return Ok(entry.body),
+        None if registry.offline => return Err(DebsError::NotInCache(pkg_name.to_owned())),
+        Some((cache, entry)) if cache.is_fresh(&entry) => return Ok(entry.body),
+        cached => cached,
+    };
+
+    if let Some(etag) = cached.as_ref().and_then(|(_, entry)| entry.etag.as_ref()) {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+
+    // held until the response is fully read
+    let _permit = registry
+        .permits
+        .acquire()
+        .await
+        .expect("the semaphore is never closed");
+
+    let resp = retry::send_with_retries(request)
+        .await
+        .map_err(|source| DebsError::Network {
+            pkg: pkg_name.to_owned(),
+            source,
+        })?;
+
+    if let (StatusCode::NOT_MODIFIED, Some((cache, mut entry))) = (resp.status(), cached) {
+        entry.fetched_at = Utc::now().timestamp();
+
+        // failing to write to the cache only means the next run will have to ask again
+        let _ = cache.store(&url, &entry);
+
+        return Ok(entry.body);
+    }
+
+    let etag = resp
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(str::to_owned);
+
+    let body: T = parse_response(pkg_name, resp).await?;
+
+    match &registry.cache {
+        Some(cache) => {
+            let entry = CacheEntry {
+                etag,
+                fetched_at: Utc::now().timestamp(),
+                body,
+            };
+
+            let _ = cache.store(&url, &entry);
+
+            Ok(entry.body)
+        }
+        None => Ok(body),
     }
 }
 
+/// Reports unsuccessful responses (e.g. `401 Unauthorized` from a private registry) before attempting to parse them.
+async fn parse_response<T: DeserializeOwned>(
+    pkg_name: &str,
+    resp: reqwest::Response,
+) -> Result<T, DebsError> {
+    let pkg = pkg_name.to_owned();
+
+    match resp.status() {
+        StatusCode::NOT_FOUND => return Err(DebsError::PackageNotFound(pkg)),
+        status if !status.is_success() => return Err(DebsError::RegistryHttp { pkg, status }),
+        _ => {}
+    }
+
+    let text = match resp.text().await {
+        Ok(text) => text,
+        Err(source) => return Err(DebsError::Network { pkg, source }),
+    };
+
+    serde_json::from_str(text.as_str()).map_err(|source| DebsError::InvalidMetadata { pkg, source })
+}
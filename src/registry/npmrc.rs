@@ -0,0 +1,101 @@
+//! Minimal reader for npm's [`.npmrc`] configuration files.
+//!
+//! Only `key=value` lines are supported, which covers every setting debs cares about.
+//! Lines starting with `#` or `;` are comments, and `${VAR}` in values is replaced
+//! with the content of the `VAR` environment variable (commonly used for auth tokens).
+//!
+//! [`.npmrc`]: https://docs.npmjs.com/cli/configuring-npm/npmrc
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::DebsError;
+
+/// Location of the user-level `.npmrc`: `$NPM_CONFIG_USERCONFIG`, or `.npmrc` in the home directory.
+pub fn user_npmrc_path() -> Option<PathBuf> {
+    std::env::var_os("NPM_CONFIG_USERCONFIG")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME")
+                .or_else(|| std::env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".npmrc"))
+        })
+}
+
+/// Reads an `.npmrc` file into a map of its settings.
+pub fn parse_npmrc(path: &Path) -> Result<HashMap<String, String>, DebsError> {
+    let content = std::fs::read_to_string(path).map_err(|source| DebsError::Io {
+        path: path.to_owned(),
+        source,
+    })?;
+
+    Ok(parse_npmrc_str(&content))
+}
+
+fn parse_npmrc_str(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.starts_with(';'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_owned(), expand_env(unquote(value.trim()))))
+        .collect()
+}
+
+/// npm accepts both quoted and unquoted values, e.g. `registry="https://..."`.
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Replaces `${VAR}` with the value of the environment variable `VAR` (or nothing if unset).
+fn expand_env(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        match rest[start..].find('}') {
+            Some(len) => {
+                res.push_str(&rest[..start]);
+                res.push_str(&std::env::var(&rest[start + 2..start + len]).unwrap_or_default());
+                rest = &rest[start + len + 1..];
+            }
+            None => break,
+        }
+    }
+
+    res.push_str(rest);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_key_values() {
+        let config = parse_npmrc_str(
+            "# comment\n; other comment\n\nregistry = \"https://npm.example.com/\"\nsave-exact=true\n",
+        );
+
+        assert_eq!(config.len(), 2);
+        assert_eq!(config["registry"], "https://npm.example.com/");
+        assert_eq!(config["save-exact"], "true");
+    }
+
+    #[test]
+    fn should_expand_env_vars() {
+        let path = std::env::var("PATH").unwrap_or_default();
+
+        assert_eq!(expand_env("a${PATH}b"), format!("a{path}b"));
+        assert_eq!(expand_env("${DEBS_SURELY_UNDEFINED_VAR}"), "".to_owned());
+        assert_eq!(expand_env("${unclosed"), "${unclosed");
+    }
+
+    #[test]
+    fn should_return_error_on_missing_npmrc() {
+        assert!(parse_npmrc(Path::new("missing-path/.npmrc")).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Retry policy for registry requests.
+//!
+//! Registries answer bursts of requests with `429 Too Many Requests`, and mirrors under load
+//! with `5xx` errors or reset connections. These are retried with an exponential backoff
+//! (500ms, 1s, 2s, ...), or after the delay asked for by the registry in the [`Retry-After`] header.
+//!
+//! [`Retry-After`]: https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Retry-After
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+/// Number of retries after the first attempt.
+pub static MAX_RETRIES: u32 = 3;
+
+static BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Upper bound for any delay, including the ones asked for with `Retry-After`.
+static MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Sends `request`, retrying on rate limiting, server errors and connection failures.
+///
+/// After the last retry, the response is returned whatever its status, for the caller to report.
+pub async fn send_with_retries(request: RequestBuilder) -> reqwest::Result<Response> {
+    let mut attempt = 0;
+
+    loop {
+        // GET requests have no body, so they can always be cloned
+        let current = request
+            .try_clone()
+            .expect("requests without a streamed body can be cloned");
+
+        let retry_after = match current.send().await {
+            Ok(resp) if attempt < MAX_RETRIES && is_retryable(resp.status()) => resp
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after),
+            Ok(resp) => return Ok(resp),
+            Err(e)
+                if attempt < MAX_RETRIES
+                    && (e.is_timeout() || e.is_connect() || e.is_request()) =>
+            {
+                None
+            }
+            Err(e) => return Err(e),
+        };
+
+        tokio::time::sleep(retry_delay(attempt, retry_after)).await;
+
+        attempt += 1;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Delay before retry number `attempt + 1`.
+fn retry_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+    retry_after
+        .unwrap_or_else(|| BASE_DELAY * 2u32.saturating_pow(attempt))
+        .min(MAX_DELAY)
+}
+
+/// `Retry-After` is either an amount of seconds, or an HTTP date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => DateTime::parse_from_rfc2822(value.trim()).ok().map(|date| {
+            (date.with_timezone(&Utc) - Utc::now())
+                .to_std()
+                .unwrap_or_default()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_retry_rate_limiting_and_server_errors() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::OK));
+    }
+
+    #[test]
+    fn should_back_off_exponentially() {
+        assert_eq!(retry_delay(0, None), Duration::from_millis(500));
+        assert_eq!(retry_delay(1, None), Duration::from_secs(1));
+        assert_eq!(retry_delay(2, None), Duration::from_secs(2));
+        assert_eq!(retry_delay(10, None), MAX_DELAY);
+    }
+
+    #[test]
+    fn should_honour_retry_after() {
+        assert_eq!(parse_retry_after("3"), Some(Duration::from_secs(3)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon"), None);
+
+        assert_eq!(
+            retry_delay(0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(retry_delay(0, Some(Duration::from_secs(3600))), MAX_DELAY);
+    }
+}
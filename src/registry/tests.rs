@@ -2,7 +2,7 @@ use super::*;
 
 #[tokio::test(flavor = "multi_thread")]
 async fn should_return_react_info() {
-    let react_metadata = pkg_info("react").await;
+    let react_metadata = pkg_info(&Registry::default(), "react").await;
 
     assert!(react_metadata.is_ok());
 
@@ -17,44 +17,141 @@ async fn should_return_react_info() {
 
 #[tokio::test(flavor = "multi_thread")]
 async fn pkg_info_can_return_error() {
-    let res = pkg_info("ReAcT").await;
+    let res = pkg_info(&Registry::default(), "ReAcT").await;
 
     assert!(res.is_err())
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn should_return_react_version_info() {
-    let react = pkg_version_info("react", "0.8.0").await;
+async fn should_return_react_abbreviated_info() {
+    let react = pkg_abbreviated_info(&Registry::default(), "react").await;
 
     assert!(react.is_ok());
 
     let react = react.unwrap();
 
-    assert_eq!(react.version, "0.8.0");
+    assert_eq!(react.name, "react");
 
-    assert!(react.deprecated.is_none());
+    assert_eq!(react.versions["0.8.0"].version, "0.8.0");
 
-    let querystring = pkg_version_info("querystring", "0.2.0").await;
+    assert!(react.versions["0.8.0"].deprecated.is_none());
+
+    assert!(!react.versions.contains_key("A.8.0"));
+
+    let querystring = pkg_abbreviated_info(&Registry::default(), "querystring").await;
 
     assert!(querystring.is_ok());
 
     let querystring = querystring.unwrap();
 
-    assert_eq!(querystring.version, "0.2.0");
-
-    assert!(querystring.deprecated.is_some())
+    assert!(querystring.versions["0.2.0"].deprecated.is_some())
 }
 
 #[tokio::test(flavor = "multi_thread")]
-async fn should_return_error_for_fake_version() {
-    let res = pkg_version_info("react", "A.8.0").await;
+async fn should_return_error_for_fake_pkg() {
+    let res = pkg_abbreviated_info(&Registry::default(), "ReAcT").await;
 
     assert!(res.is_err())
 }
 
-#[tokio::test(flavor = "multi_thread")]
-async fn should_return_error_for_fake_pkg() {
-    let res = pkg_version_info("ReAcT", "0.1.0").await;
+#[test]
+fn registry_url_should_end_with_slash() {
+    assert_eq!(
+        Registry::new("https://npm.example.com").url_for("react"),
+        "https://npm.example.com/"
+    );
+    assert_eq!(
+        Registry::new("https://npm.example.com/").url_for("react"),
+        "https://npm.example.com/"
+    );
+}
 
-    assert!(res.is_err())
+#[test]
+fn cli_registry_should_take_precedence() {
+    let registry = Registry::resolve(
+        Some("https://cli.example.com"),
+        Path::new("test-assets/registry/"),
+    );
+
+    assert_eq!(registry.url_for("react"), "https://cli.example.com/");
+}
+
+#[test]
+fn should_read_registry_from_npmrc() {
+    // `DEBS_REGISTRY` would take precedence over `.npmrc`
+    if std::env::var(REGISTRY_ENV_VAR).is_ok() {
+        return;
+    }
+
+    let registry = Registry::from_config(None, fixture_npmrc());
+
+    assert_eq!(registry.url_for("react"), "https://npm.example.com/");
+
+    let registry = Registry::from_config(None, HashMap::new());
+
+    assert_eq!(registry.url_for("react"), DEFAULT_REGISTRY);
+}
+
+#[test]
+fn should_pick_scope_registry() {
+    let registry = Registry::from_config(Some("https://cli.example.com"), fixture_npmrc());
+
+    assert_eq!(
+        registry.url_for("@reaqta/express-brute"),
+        "https://npm.reaqta.example.com/private/"
+    );
+    assert_eq!(registry.url_for("@babel/core"), "https://cli.example.com/");
+    assert_eq!(registry.url_for("react"), "https://cli.example.com/");
+}
+
+#[test]
+fn should_pick_auth_token() {
+    let registry = Registry::from_config(None, fixture_npmrc());
+
+    // matched by dropping path segments until `//npm.reaqta.example.com/private/`
+    assert_eq!(
+        registry.auth_token_for("https://npm.reaqta.example.com/private/nested/"),
+        Some("secret-token")
+    );
+    assert_eq!(
+        registry.auth_token_for(registry.url_for("@reaqta/express-brute")),
+        Some("secret-token")
+    );
+    assert_eq!(
+        registry.auth_token_for("https://npm.reaqta.example.com/"),
+        None
+    );
+    assert_eq!(registry.auth_token_for(registry.url_for("react")), None);
+}
+
+#[test]
+fn should_encode_scoped_pkg_names() {
+    let registry = Registry::from_config(None, fixture_npmrc());
+
+    let request = registry
+        .get("@reaqta/express-brute", ABBREVIATED_METADATA)
+        .build();
+
+    assert!(request.is_ok());
+
+    let request = request.unwrap();
+
+    assert_eq!(
+        request.url().as_str(),
+        "https://npm.reaqta.example.com/private/@reaqta%2fexpress-brute"
+    );
+    assert_eq!(
+        request.headers()["accept"].to_str().ok(),
+        Some(ABBREVIATED_METADATA)
+    );
+    assert_eq!(
+        request.headers()["authorization"].to_str().ok(),
+        Some("Bearer secret-token")
+    );
+}
+
+// UTILS
+
+fn fixture_npmrc() -> HashMap<String, String> {
+    npmrc::parse_npmrc(Path::new("test-assets/registry/.npmrc")).unwrap()
 }
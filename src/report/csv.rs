@@ -0,0 +1,263 @@
+//! CSV (`--format csv`) and TSV (`--format tsv`) output, to import into spreadsheets.
+//!
+//! The first row holds the names of the columns, followed by a row per reported package of each workspace:
+//!
+//! ``` csv
+//! workspace,category,required_by,name,local_version,publication_local_version,age_local_version,latest_version,publication_latest_version,age_latest_version
+//! frontend/,prod,,pluralize,7.0.0,2017-08-20,6,8.0.0,2022-06-07,1
+//! frontend/,transitive,url,querystring,0.2.0,2015-08-20,8,0.2.1,2021-03-01,2
+//! ```
+//!
+//! The columns after `name` hold the fields of the packages, named after the JSON output. Dates follow ISO 8601,
+//! and ages are counted in years. `required_by` lists the direct dependencies pulling transitive packages in,
+//! separated by `;`.
+//!
+//! CSV values are quoted following [RFC 4180]. TSV can't quote values, so tabs and line breaks are replaced by spaces.
+//! Fields of the packages starting with `=`, `+`, `-` or `@` (e.g. deprecation messages) are prefixed with `'`,
+//! so that spreadsheets don't run them as formulas. Package names and versions are left as-is, so that scoped
+//! packages can still be looked up by name.
+//!
+//! [RFC 4180]: https://www.rfc-editor.org/rfc/rfc4180
+
+use super::{Finding, WorkspaceResults};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delimiter {
+    Comma,
+    Tab,
+}
+
+impl Delimiter {
+    fn as_char(&self) -> char {
+        match self {
+            Delimiter::Comma => ',',
+            Delimiter::Tab => '\t',
+        }
+    }
+
+    fn escape(&self, value: &str) -> String {
+        match self {
+            Delimiter::Comma if value.contains([',', '"', '\r', '\n']) => {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            }
+            Delimiter::Comma => value.to_owned(),
+            Delimiter::Tab => value.replace(['\t', '\r', '\n'], " "),
+        }
+    }
+}
+
+pub fn write_csv<T: Finding>(
+    results: &[WorkspaceResults<T>],
+    delimiter: Delimiter,
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let header: Vec<String> = [
+        "workspace",
+        "category",
+        "required_by",
+        "name",
+        "local_version",
+    ]
+    .into_iter()
+    .chain(T::FIELDS.iter().map(|(name, _)| *name))
+    .map(str::to_owned)
+    .collect();
+
+    write_row(&header, delimiter, &mut writer)?;
+
+    for results in results {
+        let direct = results.direct.iter().flat_map(|(category, pkgs)| {
+            pkgs.iter()
+                .map(|pkg| (category.as_str(), String::new(), pkg))
+        });
+
+        let transitive = results
+            .transitive
+            .iter()
+            .flatten()
+            .map(|(pkg, required_by)| ("transitive", required_by.join(";"), pkg));
+
+        for (category, required_by, pkg) in direct.chain(transitive) {
+            let row: Vec<String> = [
+                results.workspace.clone(),
+                category.to_owned(),
+                required_by,
+                pkg.name().to_owned(),
+                pkg.local_version().to_owned(),
+            ]
+            .into_iter()
+            .chain(
+                T::FIELDS
+                    .iter()
+                    .map(|(_, value)| defuse_formula(value(pkg))),
+            )
+            .collect();
+
+            write_row(&row, delimiter, &mut writer)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prefixes free-form text that spreadsheets would run as a formula with `'`.
+fn defuse_formula(value: String) -> String {
+    match value.starts_with(['=', '+', '-', '@']) {
+        true => format!("'{value}"),
+        false => value,
+    }
+}
+
+fn write_row(
+    values: &[String],
+    delimiter: Delimiter,
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let values: Vec<String> = values.iter().map(|value| delimiter.escape(value)).collect();
+
+    writeln!(
+        writer,
+        "{}",
+        values.join(delimiter.as_char().to_string().as_str())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::*;
+    use crate::deprecated::DeprecatedPkgDetails;
+    use crate::old::OldPkgDetails;
+    use crate::types::{DepCategory, DepsByCategory};
+
+    fn workspace_results<T>(
+        direct: DepsByCategory<T>,
+        transitive: Option<Vec<(T, Vec<String>)>>,
+    ) -> WorkspaceResults<T> {
+        WorkspaceResults {
+            workspace: "frontend/".to_owned(),
+            direct,
+            transitive,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn should_write_a_row_per_old_pkg() -> Result<(), std::io::Error> {
+        let date = |year: i32, month: u32, day: u32| {
+            FixedOffset::west_opt(5 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(year, month, day, 12, 0, 0)
+                .unwrap()
+        };
+
+        let pkg = OldPkgDetails {
+            name: "pluralize".to_owned(),
+            local_version: "7.0.0".to_owned(),
+            publication_local_version: date(2017, 8, 20),
+            age_local_version: 6,
+            latest_version: "8.0.0".to_owned(),
+            publication_latest_version: date(2022, 6, 7),
+            age_latest_version: 1,
+        };
+
+        let results = [workspace_results(
+            DepsByCategory::from([
+                (DepCategory::Production, vec![pkg.clone()]),
+                (DepCategory::Development, vec![]),
+            ]),
+            Some(vec![(pkg, vec!["a".to_owned(), "b".to_owned()])]),
+        )];
+
+        let mut bytes = Vec::new();
+
+        write_csv(&results, Delimiter::Comma, &mut bytes)?;
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "workspace,category,required_by,name,local_version,publication_local_version,age_local_version,latest_version,publication_latest_version,age_latest_version
+frontend/,prod,,pluralize,7.0.0,2017-08-20,6,8.0.0,2022-06-07,1
+frontend/,transitive,a;b,pluralize,7.0.0,2017-08-20,6,8.0.0,2022-06-07,1
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_escape_values() -> Result<(), std::io::Error> {
+        let results = [workspace_results(
+            DepsByCategory::from([(
+                DepCategory::Production,
+                vec![DeprecatedPkgDetails {
+                    name: "request".to_owned(),
+                    local_version: "2.88.2".to_owned(),
+                    deprecation_message: Some("use \"got\",\tor\nfetch".to_owned()),
+                }],
+            )]),
+            None,
+        )];
+
+        let mut csv = Vec::new();
+        let mut tsv = Vec::new();
+
+        write_csv(&results, Delimiter::Comma, &mut csv)?;
+        write_csv(&results, Delimiter::Tab, &mut tsv)?;
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "workspace,category,required_by,name,local_version,deprecation_message
+frontend/,prod,,request,2.88.2,\"use \"\"got\"\",\tor\nfetch\"
+"
+        );
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            "workspace\tcategory\trequired_by\tname\tlocal_version\tdeprecation_message
+frontend/\tprod\t\trequest\t2.88.2\tuse \"got\", or fetch
+"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_prefix_values_read_as_formulas() -> Result<(), std::io::Error> {
+        let pkg = |name: &str, message: &str| DeprecatedPkgDetails {
+            name: name.to_owned(),
+            local_version: "1.0.0".to_owned(),
+            deprecation_message: Some(message.to_owned()),
+        };
+
+        let results = [workspace_results(
+            DepsByCategory::from([(
+                DepCategory::Production,
+                vec![
+                    pkg("@scope/a", "=HYPERLINK(\"https://example.com\")"),
+                    pkg("b", "+1"),
+                    pkg("c", "-1, or use d"),
+                    pkg("d", "@scope/a replaces it"),
+                    pkg("e", "a=b"),
+                ],
+            )]),
+            None,
+        )];
+
+        let mut bytes = Vec::new();
+
+        write_csv(&results, Delimiter::Comma, &mut bytes)?;
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "workspace,category,required_by,name,local_version,deprecation_message
+frontend/,prod,,@scope/a,1.0.0,\"'=HYPERLINK(\"\"https://example.com\"\")\"
+frontend/,prod,,b,1.0.0,'+1
+frontend/,prod,,c,1.0.0,\"'-1, or use d\"
+frontend/,prod,,d,1.0.0,'@scope/a replaces it
+frontend/,prod,,e,1.0.0,a=b
+"
+        );
+
+        Ok(())
+    }
+}
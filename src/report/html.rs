@@ -0,0 +1,183 @@
+//! HTML output (`--format html`), a standalone page to publish as a CI artifact.
+//!
+//! The page holds the same summary, tables and lists as the [Markdown output](super::markdown).
+
+use super::{
+    category_totals, num_reported, other_sections, table_header, table_rows, Check, Finding,
+    WorkspaceResults,
+};
+
+static STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+th { background: #f3f3f3; }
+td.number { text-align: right; }";
+
+pub fn write_html<T: Finding>(
+    check: Check,
+    results: &[WorkspaceResults<T>],
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let title = check.title();
+
+    writeln!(writer, "<!DOCTYPE html>")?;
+    writeln!(writer, "<html lang=\"en\">")?;
+    writeln!(writer, "<head>")?;
+    writeln!(writer, "<meta charset=\"utf-8\">")?;
+    writeln!(writer, "<title>{title}</title>")?;
+    writeln!(writer, "<style>\n{STYLE}\n</style>")?;
+    writeln!(writer, "</head>")?;
+    writeln!(writer, "<body>")?;
+    writeln!(writer, "<h1>{title}</h1>")?;
+
+    writeln!(writer, "<table>")?;
+    write_header_row(&["Workspace", "Reported", "Could not check"], &mut writer)?;
+
+    for results in results {
+        writeln!(
+            writer,
+            "<tr><td>{}</td><td class=\"number\">{}</td><td class=\"number\">{}</td></tr>",
+            escape(&results.workspace),
+            num_reported(results),
+            results.unchecked.len()
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "<tr><th>Total</th><th class=\"number\">{}</th><th class=\"number\">{}</th></tr>",
+        results.iter().map(num_reported).sum::<usize>(),
+        results
+            .iter()
+            .map(|results| results.unchecked.len())
+            .sum::<usize>()
+    )?;
+    writeln!(writer, "</table>")?;
+
+    for results in results {
+        writeln!(writer, "<h2>{}</h2>", escape(&results.workspace))?;
+
+        let rows = table_rows(results);
+
+        if rows.is_empty() {
+            writeln!(writer, "<p>No {}.</p>", title.to_lowercase())?;
+        } else {
+            writeln!(writer, "<table>")?;
+            write_header_row(&table_header::<T>(), &mut writer)?;
+
+            for row in &rows {
+                let cells: String = row
+                    .iter()
+                    .map(|cell| format!("<td>{}</td>", escape(cell)))
+                    .collect();
+
+                writeln!(writer, "<tr>{cells}</tr>")?;
+            }
+
+            writeln!(writer, "</table>")?;
+        }
+
+        writeln!(writer, "<p>Total: {}</p>", category_totals(results))?;
+
+        for (title, items) in other_sections(results) {
+            writeln!(writer, "<h3>{title}</h3>")?;
+            writeln!(writer, "<ul>")?;
+
+            for item in items {
+                writeln!(writer, "<li>{}</li>", escape(&item))?;
+            }
+
+            writeln!(writer, "</ul>")?;
+        }
+    }
+
+    writeln!(writer, "</body>")?;
+    writeln!(writer, "</html>")
+}
+
+fn write_header_row(cells: &[&str], mut writer: impl std::io::Write) -> Result<(), std::io::Error> {
+    let cells: String = cells
+        .iter()
+        .map(|cell| format!("<th>{}</th>", escape(cell)))
+        .collect();
+
+    writeln!(writer, "<tr>{cells}</tr>")
+}
+
+/// Escapes the characters with a meaning in HTML, e.g. in deprecation messages.
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut acc, c| {
+            match c {
+                '&' => acc.push_str("&amp;"),
+                '<' => acc.push_str("&lt;"),
+                '>' => acc.push_str("&gt;"),
+                '"' => acc.push_str("&quot;"),
+                '\'' => acc.push_str("&#39;"),
+                _ => acc.push(c),
+            }
+
+            acc
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::*;
+    use crate::old::OldPkgDetails;
+    use crate::types::{DepCategory, DepsByCategory, UnmetPeerPkg};
+
+    #[test]
+    fn should_write_a_table_per_workspace() -> Result<(), std::io::Error> {
+        let date = |year: i32| {
+            FixedOffset::west_opt(0)
+                .unwrap()
+                .with_ymd_and_hms(year, 8, 20, 0, 0, 0)
+                .unwrap()
+        };
+
+        let results = [WorkspaceResults {
+            workspace: "<frontend>/".to_owned(),
+            direct: DepsByCategory::from([(
+                DepCategory::Production,
+                vec![OldPkgDetails {
+                    name: "pluralize".to_owned(),
+                    local_version: "7.0.0".to_owned(),
+                    publication_local_version: date(2017),
+                    age_local_version: 6,
+                    latest_version: "8.0.0".to_owned(),
+                    publication_latest_version: date(2022),
+                    age_latest_version: 1,
+                }],
+            )]),
+            unmet_peers: vec![UnmetPeerPkg(
+                "react".to_owned(),
+                "^17.0.0".to_owned(),
+                vec![],
+            )],
+            ..Default::default()
+        }];
+
+        let mut bytes = Vec::new();
+
+        write_html(Check::Old, &results, &mut bytes)?;
+
+        let output = String::from_utf8(bytes).unwrap();
+
+        assert!(output.starts_with("<!DOCTYPE html>\n<html lang=\"en\">\n"));
+        assert!(output.contains("<title>Old dependencies</title>"));
+        assert!(output.contains("<tr><td>&lt;frontend&gt;/</td><td class=\"number\">1</td><td class=\"number\">0</td></tr>"));
+        assert!(output.contains("<h2>&lt;frontend&gt;/</h2>"));
+        assert!(output.contains("<tr><th>Category</th><th>Package</th><th>Installed</th><th>Published</th><th>Age</th><th>Latest</th><th>Gap</th></tr>"));
+        assert!(output.contains("<tr><td>prod</td><td>pluralize</td><td>7.0.0</td><td>2017-08-20</td><td>6 years</td><td>8.0.0 (2022-08-20)</td><td>5 years</td></tr>"));
+        assert!(output.contains("<p>Total: 1 prod</p>"));
+        assert!(output.contains(
+            "<h3>Unmet peer dependencies</h3>\n<ul>\n<li>react@^17.0.0 (not installed)</li>\n</ul>"
+        ));
+        assert!(output.ends_with("</body>\n</html>\n"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+//! JSON output (`--format json`), one document for every workspace:
+//!
+//! ``` json
+//! {
+//!     "schema_version": 1,
+//!     "check": "old",
+//!     "workspaces": [
+//!         {
+//!             "workspace": "frontend/",
+//!             "packages": [
+//!                 {
+//!                     "category": "prod",
+//!                     "name": "pluralize",
+//!                     "local_version": "7.0.0",
+//!                     ...
+//!                 },
+//!                 {
+//!                     "category": "transitive",
+//!                     "name": "querystring",
+//!                     ...
+//!                     "required_by": ["url"]
+//!                 }
+//!             ],
+//!             "unmet_peer_dependencies": [{ "name": "react", "range": "^17.0.0", "installed": ["18.2.0"] }],
+//!             "non_registry_dependencies": [{ "name": "common", "kind": "file", "spec": "file:../common" }],
+//!             "errors": [{ "name": "left-pad", "version": "9.9.9", "reason": "..." }]
+//!         }
+//!     ]
+//! }
+//! ```
+//!
+//! `category` is one of `prod`, `dev`, `optional`, `peer`, `bundled` or `transitive`, and `required_by` is only set
+//! for transitive packages. The other fields of `packages` depend on the `check`:
+//!
+//! - `old`: `name`, `local_version`, `publication_local_version`, `age_local_version`, `latest_version`,
+//!   `publication_latest_version` and `age_latest_version`, dates following RFC 3339 and ages counted in years
+//! - `deprecated`: `name`, `local_version` and `deprecation_message`, `null` when the registry gives no message
+//!
+//! [SCHEMA_VERSION] is bumped whenever fields are renamed or removed. New fields may be added without bumping it.
+
+use serde::Serialize;
+
+use super::{Check, WorkspaceResults};
+use crate::package_json::NonRegistryPkg;
+use crate::types::{PkgName, PkgNameAndVersion, UncheckedPkg, UnmetPeerPkg};
+
+/// Version of the JSON schema, see the [module documentation](self).
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Report<'a, T> {
+    schema_version: u32,
+    check: Check,
+    workspaces: Vec<Workspace<'a, T>>,
+}
+
+#[derive(Serialize)]
+struct Workspace<'a, T> {
+    workspace: &'a str,
+    packages: Vec<Package<'a, T>>,
+    unmet_peer_dependencies: Vec<UnmetPeer<'a>>,
+    non_registry_dependencies: Vec<NonRegistry<'a>>,
+    errors: Vec<Error<'a>>,
+}
+
+#[derive(Serialize)]
+struct Package<'a, T> {
+    category: &'static str,
+    #[serde(flatten)]
+    details: &'a T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    required_by: Option<&'a [PkgName]>,
+}
+
+#[derive(Serialize)]
+struct UnmetPeer<'a> {
+    name: &'a str,
+    range: &'a str,
+    installed: &'a [String],
+}
+
+#[derive(Serialize)]
+struct NonRegistry<'a> {
+    name: &'a str,
+    kind: &'static str,
+    spec: String,
+}
+
+#[derive(Serialize)]
+struct Error<'a> {
+    name: &'a str,
+    version: &'a str,
+    reason: String,
+}
+
+pub fn write_json<T: Serialize>(
+    check: Check,
+    results: &[WorkspaceResults<T>],
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let report = Report {
+        schema_version: SCHEMA_VERSION,
+        check,
+        workspaces: results.iter().map(to_workspace).collect(),
+    };
+
+    serde_json::to_writer_pretty(&mut writer, &report)?;
+
+    writeln!(writer)
+}
+
+fn to_workspace<T>(results: &WorkspaceResults<T>) -> Workspace<'_, T> {
+    let direct = results.direct.iter().flat_map(|(category, pkgs)| {
+        pkgs.iter().map(|details| Package {
+            category: category.as_str(),
+            details,
+            required_by: None,
+        })
+    });
+
+    let transitive = results
+        .transitive
+        .iter()
+        .flatten()
+        .map(|(details, required_by)| Package {
+            category: "transitive",
+            details,
+            required_by: Some(required_by),
+        });
+
+    Workspace {
+        workspace: &results.workspace,
+        packages: direct.chain(transitive).collect(),
+        unmet_peer_dependencies: results
+            .unmet_peers
+            .iter()
+            .map(|UnmetPeerPkg(name, range, installed)| UnmetPeer {
+                name,
+                range,
+                installed,
+            })
+            .collect(),
+        non_registry_dependencies: results
+            .non_registry
+            .iter()
+            .map(|NonRegistryPkg(name, spec)| NonRegistry {
+                name,
+                kind: spec.kind(),
+                spec: spec.to_string(),
+            })
+            .collect(),
+        errors: results
+            .unchecked
+            .iter()
+            .map(
+                |UncheckedPkg(PkgNameAndVersion(name, version), error)| Error {
+                    name,
+                    version,
+                    reason: error.reason(),
+                },
+            )
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::error::DebsError;
+    use crate::package_json::DepSpec;
+    use crate::types::{DepCategory, DepsByCategory};
+
+    #[derive(Serialize)]
+    struct Details {
+        name: &'static str,
+    }
+
+    #[test]
+    fn should_write_every_section_of_workspaces() -> Result<(), std::io::Error> {
+        let results = [WorkspaceResults {
+            workspace: "frontend/".to_owned(),
+            direct: DepsByCategory::from([
+                (DepCategory::Production, vec![Details { name: "a" }]),
+                (DepCategory::Development, vec![]),
+            ]),
+            transitive: Some(vec![(Details { name: "b" }, vec!["a".to_owned()])]),
+            unmet_peers: vec![UnmetPeerPkg(
+                "react".to_owned(),
+                "^17.0.0".to_owned(),
+                vec!["18.2.0".to_owned()],
+            )],
+            non_registry: vec![NonRegistryPkg(
+                "common".to_owned(),
+                DepSpec::File("file:../common".to_owned()),
+            )],
+            unchecked: vec![UncheckedPkg(
+                PkgNameAndVersion("c".to_owned(), "1.0.0".to_owned()),
+                Arc::new(DebsError::PackageNotFound("c".to_owned())),
+            )],
+            ..Default::default()
+        }];
+
+        let mut bytes = Vec::new();
+
+        write_json(Check::Deprecated, &results, &mut bytes)?;
+
+        let output: serde_json::Value = serde_json::from_slice(&bytes)?;
+
+        assert_eq!(
+            output,
+            json!({
+                "schema_version": 1,
+                "check": "deprecated",
+                "workspaces": [{
+                    "workspace": "frontend/",
+                    "packages": [
+                        { "category": "prod", "name": "a" },
+                        { "category": "transitive", "name": "b", "required_by": ["a"] }
+                    ],
+                    "unmet_peer_dependencies": [
+                        { "name": "react", "range": "^17.0.0", "installed": ["18.2.0"] }
+                    ],
+                    "non_registry_dependencies": [
+                        { "name": "common", "kind": "file", "spec": "file:../common" }
+                    ],
+                    "errors": [
+                        { "name": "c", "version": "1.0.0", "reason": "not found in the registry" }
+                    ]
+                }]
+            })
+        );
+
+        Ok(())
+    }
+}
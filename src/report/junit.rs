@@ -0,0 +1,310 @@
+//! JUnit XML output (`--format junit`), for CI servers showing dependency checks next to unit tests.
+//!
+//! Each workspace is a test suite, and each checked dependency a test case named after the package and its
+//! installed version, with its category as class name:
+//!
+//! ``` xml
+//! <?xml version="1.0" encoding="UTF-8"?>
+//! <testsuites name="Old dependencies" tests="3" failures="1" errors="1" skipped="0">
+//!   <testsuite name="frontend/" tests="3" failures="1" errors="1" skipped="0">
+//!     <testcase classname="prod" name="pluralize@7.0.0">
+//!       <failure type="old" message="pluralize@7.0.0 is 6 years old (20/08/2017), ..."/>
+//!     </testcase>
+//!     <testcase classname="prod" name="react@18.2.0"/>
+//!     <testcase classname="dev" name="left-pad@9.9.9">
+//!       <error message="version not found in the registry"/>
+//!     </testcase>
+//!   </testsuite>
+//! </testsuites>
+//! ```
+//!
+//! Reported packages fail, and packages that could not be checked are errors. Non-registry dependencies are
+//! skipped test cases, as they can't be checked against the registry.
+
+use super::{Check, Finding, WorkspaceResults};
+use crate::package_json::NonRegistryPkg;
+use crate::types::{PkgNameAndVersion, TransitivePkg, UncheckedPkg};
+
+enum Outcome {
+    Passed,
+    Failed(String),
+    Error(String),
+    Skipped(String),
+}
+
+struct TestCase {
+    classname: &'static str,
+    name: String,
+    outcome: Outcome,
+}
+
+#[derive(Default)]
+struct Counts {
+    tests: usize,
+    failures: usize,
+    errors: usize,
+    skipped: usize,
+}
+
+impl Counts {
+    fn of(cases: &[TestCase]) -> Counts {
+        cases.iter().fold(Counts::default(), |mut counts, case| {
+            counts.tests += 1;
+
+            match case.outcome {
+                Outcome::Passed => {}
+                Outcome::Failed(_) => counts.failures += 1,
+                Outcome::Error(_) => counts.errors += 1,
+                Outcome::Skipped(_) => counts.skipped += 1,
+            }
+
+            counts
+        })
+    }
+
+    fn add(mut self, other: Counts) -> Counts {
+        self.tests += other.tests;
+        self.failures += other.failures;
+        self.errors += other.errors;
+        self.skipped += other.skipped;
+        self
+    }
+
+    fn attributes(&self) -> String {
+        format!(
+            "tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\"",
+            self.tests, self.failures, self.errors, self.skipped
+        )
+    }
+}
+
+pub fn write_junit<T: Finding>(
+    check: Check,
+    results: &[WorkspaceResults<T>],
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let suites: Vec<(&str, Vec<TestCase>)> = results
+        .iter()
+        .map(|results| (results.workspace.as_str(), test_cases(results)))
+        .collect();
+
+    let total = suites.iter().fold(Counts::default(), |total, (_, cases)| {
+        total.add(Counts::of(cases))
+    });
+
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        writer,
+        "<testsuites name=\"{}\" {}>",
+        check.title(),
+        total.attributes()
+    )?;
+
+    for (workspace, cases) in &suites {
+        writeln!(
+            writer,
+            "  <testsuite name=\"{}\" {}>",
+            escape(workspace),
+            Counts::of(cases).attributes()
+        )?;
+
+        for case in cases {
+            let start = format!(
+                "<testcase classname=\"{}\" name=\"{}\"",
+                case.classname,
+                escape(&case.name)
+            );
+
+            let child = match &case.outcome {
+                Outcome::Passed => None,
+                Outcome::Failed(message) => Some(format!(
+                    "<failure type=\"{}\" message=\"{}\"/>",
+                    check.id(),
+                    escape(message)
+                )),
+                Outcome::Error(reason) => Some(format!("<error message=\"{}\"/>", escape(reason))),
+                Outcome::Skipped(message) => {
+                    Some(format!("<skipped message=\"{}\"/>", escape(message)))
+                }
+            };
+
+            match child {
+                None => writeln!(writer, "    {start}/>")?,
+                Some(child) => {
+                    writeln!(writer, "    {start}>")?;
+                    writeln!(writer, "      {child}")?;
+                    writeln!(writer, "    </testcase>")?;
+                }
+            }
+        }
+
+        writeln!(writer, "  </testsuite>")?;
+    }
+
+    writeln!(writer, "</testsuites>")
+}
+
+/// Test cases of every checked dependency of a workspace: direct dependencies by category, transitive
+/// dependencies, then non-registry dependencies.
+fn test_cases<T: Finding>(results: &WorkspaceResults<T>) -> Vec<TestCase> {
+    let outcome = |pkg: &PkgNameAndVersion, reported: Option<&T>| match reported {
+        Some(finding) => Outcome::Failed(finding.description()),
+        None => results
+            .unchecked
+            .iter()
+            .find(|UncheckedPkg(unchecked, _)| unchecked == pkg)
+            .map_or(Outcome::Passed, |UncheckedPkg(_, error)| {
+                Outcome::Error(error.reason())
+            }),
+    };
+
+    let direct = results.checked.iter().flat_map(|(category, pkgs)| {
+        let reported = results.direct.get(category);
+
+        pkgs.iter().map(move |pkg| TestCase {
+            classname: category.as_str(),
+            name: format!("{}@{}", pkg.0, pkg.1),
+            outcome: outcome(pkg, find_finding(reported.into_iter().flatten(), pkg)),
+        })
+    });
+
+    let transitive = results
+        .checked_transitive
+        .iter()
+        .flatten()
+        .map(|TransitivePkg(pkg, _)| TestCase {
+            classname: "transitive",
+            name: format!("{}@{}", pkg.0, pkg.1),
+            outcome: outcome(
+                pkg,
+                find_finding(
+                    results
+                        .transitive
+                        .iter()
+                        .flatten()
+                        .map(|(finding, _)| finding),
+                    pkg,
+                ),
+            ),
+        });
+
+    let non_registry = results
+        .non_registry
+        .iter()
+        .map(|NonRegistryPkg(name, spec)| TestCase {
+            classname: "non-registry",
+            name: name.clone(),
+            outcome: Outcome::Skipped(format!("{}: {spec}", spec.kind())),
+        });
+
+    direct.chain(transitive).chain(non_registry).collect()
+}
+
+/// Finds the reported package matching a checked package, if any.
+fn find_finding<'a, T: Finding>(
+    findings: impl IntoIterator<Item = &'a T>,
+    PkgNameAndVersion(name, version): &PkgNameAndVersion,
+) -> Option<&'a T> {
+    findings
+        .into_iter()
+        .find(|finding| finding.name() == name && finding.local_version() == version)
+}
+
+/// Escapes the characters with a meaning in XML attributes, e.g. in deprecation messages.
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut acc, c| {
+            match c {
+                '&' => acc.push_str("&amp;"),
+                '<' => acc.push_str("&lt;"),
+                '>' => acc.push_str("&gt;"),
+                '"' => acc.push_str("&quot;"),
+                '\'' => acc.push_str("&apos;"),
+                '\n' => acc.push_str("&#10;"),
+                _ => acc.push(c),
+            }
+
+            acc
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::deprecated::DeprecatedPkgDetails;
+    use crate::error::DebsError;
+    use crate::package_json::DepSpec;
+    use crate::types::{DepCategory, DepsByCategory};
+
+    fn pkg(name: &str, version: &str) -> PkgNameAndVersion {
+        PkgNameAndVersion(name.to_owned(), version.to_owned())
+    }
+
+    #[test]
+    fn should_write_a_test_case_per_checked_pkg() -> Result<(), std::io::Error> {
+        let results = [WorkspaceResults {
+            workspace: "backend/".to_owned(),
+            direct: DepsByCategory::from([
+                (
+                    DepCategory::Production,
+                    vec![DeprecatedPkgDetails {
+                        name: "request".to_owned(),
+                        local_version: "2.88.2".to_owned(),
+                        deprecation_message: Some("use <fetch> & \"got\"".to_owned()),
+                    }],
+                ),
+                (DepCategory::Development, vec![]),
+            ]),
+            transitive: Some(vec![]),
+            unmet_peers: vec![],
+            non_registry: vec![NonRegistryPkg(
+                "common".to_owned(),
+                DepSpec::File("file:../common".to_owned()),
+            )],
+            unchecked: vec![UncheckedPkg(
+                pkg("left-pad", "9.9.9"),
+                Arc::new(DebsError::VersionNotFound(pkg("left-pad", "9.9.9"))),
+            )],
+            checked: DepsByCategory::from([
+                (
+                    DepCategory::Production,
+                    vec![pkg("request", "2.88.2"), pkg("express", "4.18.2")],
+                ),
+                (DepCategory::Development, vec![pkg("left-pad", "9.9.9")]),
+            ]),
+            checked_transitive: Some(vec![TransitivePkg(
+                pkg("ms", "2.1.3"),
+                vec!["express".to_owned()],
+            )]),
+        }];
+
+        let mut bytes = Vec::new();
+
+        write_junit(Check::Deprecated, &results, &mut bytes)?;
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="Deprecated dependencies" tests="5" failures="1" errors="1" skipped="1">
+  <testsuite name="backend/" tests="5" failures="1" errors="1" skipped="1">
+    <testcase classname="prod" name="request@2.88.2">
+      <failure type="deprecated" message="request@2.88.2 is deprecated: use &lt;fetch&gt; &amp; &quot;got&quot;"/>
+    </testcase>
+    <testcase classname="prod" name="express@4.18.2"/>
+    <testcase classname="dev" name="left-pad@9.9.9">
+      <error message="version not found in the registry"/>
+    </testcase>
+    <testcase classname="transitive" name="ms@2.1.3"/>
+    <testcase classname="non-registry" name="common">
+      <skipped message="file: file:../common"/>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+
+        Ok(())
+    }
+}
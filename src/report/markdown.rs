@@ -0,0 +1,197 @@
+//! Markdown output (`--format markdown`), to paste into pull requests or dependency reviews.
+//!
+//! The report starts with a summary of every workspace, followed by a section per workspace
+//! holding the table of reported packages, their number by category, and lists of the packages
+//! with unmet peer dependencies, non-registry dependencies or packages that could not be checked.
+
+use super::{
+    category_totals, num_reported, other_sections, table_header, table_rows, Check, Finding,
+    WorkspaceResults,
+};
+
+pub fn write_markdown<T: Finding>(
+    check: Check,
+    results: &[WorkspaceResults<T>],
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    writeln!(writer, "# {}\n", check.title())?;
+
+    write_row(&["Workspace", "Reported", "Could not check"], &mut writer)?;
+    writeln!(writer, "| --- | ---: | ---: |")?;
+
+    for results in results {
+        write_row(
+            &[
+                &results.workspace,
+                &num_reported(results).to_string(),
+                &results.unchecked.len().to_string(),
+            ],
+            &mut writer,
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "| **Total** | **{}** | **{}** |",
+        results.iter().map(num_reported).sum::<usize>(),
+        results
+            .iter()
+            .map(|results| results.unchecked.len())
+            .sum::<usize>()
+    )?;
+
+    for results in results {
+        writeln!(writer, "\n## {}\n", escape(&results.workspace))?;
+
+        let rows = table_rows(results);
+
+        if rows.is_empty() {
+            writeln!(writer, "No {}.", check.title().to_lowercase())?;
+        } else {
+            let header = table_header::<T>();
+
+            write_row(&header, &mut writer)?;
+            writeln!(writer, "|{}", " --- |".repeat(header.len()))?;
+
+            for row in &rows {
+                write_row(row, &mut writer)?;
+            }
+        }
+
+        writeln!(writer, "\nTotal: {}", category_totals(results))?;
+
+        for (title, items) in other_sections(results) {
+            writeln!(writer, "\n### {title}\n")?;
+
+            for item in items {
+                writeln!(writer, "- {}", escape(&item))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn write_row(
+    cells: &[impl AsRef<str>],
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let cells: Vec<String> = cells.iter().map(|cell| escape(cell.as_ref())).collect();
+
+    writeln!(writer, "| {} |", cells.join(" | "))
+}
+
+/// Keeps text on a single line, and from being read as Markdown (e.g. `|` splitting a table cell).
+fn escape(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .fold(String::with_capacity(text.len()), |mut acc, c| {
+            if matches!(c, '\\' | '|' | '*' | '_' | '`' | '<' | '>' | '[' | ']') {
+                acc.push('\\');
+            }
+
+            acc.push(c);
+            acc
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::deprecated::DeprecatedPkgDetails;
+    use crate::error::DebsError;
+    use crate::package_json::{DepSpec, NonRegistryPkg};
+    use crate::types::{DepCategory, DepsByCategory, PkgNameAndVersion, UncheckedPkg};
+
+    #[test]
+    fn should_write_a_table_per_workspace() -> Result<(), std::io::Error> {
+        let results = [
+            WorkspaceResults {
+                workspace: "backend/".to_owned(),
+                direct: DepsByCategory::from([
+                    (
+                        DepCategory::Production,
+                        vec![DeprecatedPkgDetails {
+                            name: "request".to_owned(),
+                            local_version: "2.88.2".to_owned(),
+                            deprecation_message: Some(
+                                "request has been deprecated | see #3142".to_owned(),
+                            ),
+                        }],
+                    ),
+                    (DepCategory::Development, vec![]),
+                ]),
+                transitive: Some(vec![(
+                    DeprecatedPkgDetails {
+                        name: "querystring".to_owned(),
+                        local_version: "0.2.0".to_owned(),
+                        deprecation_message: None,
+                    },
+                    vec!["url".to_owned()],
+                )]),
+                non_registry: vec![NonRegistryPkg(
+                    "common".to_owned(),
+                    DepSpec::File("file:../common".to_owned()),
+                )],
+                unchecked: vec![UncheckedPkg(
+                    PkgNameAndVersion("left-pad".to_owned(), "9.9.9".to_owned()),
+                    Arc::new(DebsError::VersionNotFound(PkgNameAndVersion(
+                        "left-pad".to_owned(),
+                        "9.9.9".to_owned(),
+                    ))),
+                )],
+                ..Default::default()
+            },
+            WorkspaceResults {
+                workspace: "frontend/".to_owned(),
+                direct: DepsByCategory::from([(DepCategory::Production, vec![])]),
+                ..Default::default()
+            },
+        ];
+
+        let mut bytes = Vec::new();
+
+        write_markdown(Check::Deprecated, &results, &mut bytes)?;
+
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            r"# Deprecated dependencies
+
+| Workspace | Reported | Could not check |
+| --- | ---: | ---: |
+| backend/ | 2 | 1 |
+| frontend/ | 0 | 0 |
+| **Total** | **2** | **1** |
+
+## backend/
+
+| Category | Package | Installed | Deprecation message |
+| --- | --- | --- | --- |
+| prod | request | 2.88.2 | request has been deprecated \| see #3142 |
+| transitive (url) | querystring | 0.2.0 |  |
+
+Total: 1 prod, 0 dev, 1 transitive
+
+### Non-registry dependencies
+
+- common (file: file:../common)
+
+### Could not check
+
+- left-pad@9.9.9: version not found in the registry
+
+## frontend/
+
+No deprecated dependencies.
+
+Total: 0 prod
+"
+        );
+
+        Ok(())
+    }
+}
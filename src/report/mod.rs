@@ -0,0 +1,289 @@
+//! Module rendering the results of `old` and `deprecated` for other tools to read, instead of the text output.
+//!
+//! The entry point to this module is [write_report].
+//!
+//! Both commands first gather their results for each workspace in a [WorkspaceResults], then hand them to this
+//! module along with the requested [Format], and with how they are written as text, which differs between them.
+//! The sections of the text output both commands share are found in [`text`].
+
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::package_json::NonRegistryPkg;
+use crate::types::{
+    DepsByCategory, PkgName, PkgNameAndVersion, TransitivePkg, UncheckedPkg, UnmetPeerPkg,
+};
+
+mod csv;
+mod html;
+mod json;
+mod junit;
+mod markdown;
+mod sarif;
+pub mod text;
+
+/// Output format of `old` and `deprecated`, given to `--format`.
+///
+/// Variants are documented for `--help`; each format but text is written by the module of the same name
+/// ([`csv`](self::csv) writing TSV as well).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// Human-readable text, the default.
+    Text,
+    /// Versioned JSON schema.
+    Json,
+    /// SARIF 2.1.0, for code-scanning tools.
+    Sarif,
+    /// Tables for pull requests and documents.
+    Markdown,
+    /// Standalone page with the same tables as Markdown.
+    Html,
+    /// Comma-separated values, for spreadsheets.
+    Csv,
+    /// Tab-separated values, for spreadsheets.
+    Tsv,
+    /// JUnit XML, for CI test reports.
+    Junit,
+}
+
+/// Command whose results are reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Check {
+    Old,
+    Deprecated,
+}
+
+impl Check {
+    /// Identifier of the check, e.g. naming SARIF rules or JUnit failure types.
+    fn id(&self) -> &'static str {
+        match self {
+            Check::Old => "old",
+            Check::Deprecated => "deprecated",
+        }
+    }
+
+    /// Title of reports, also naming what they list.
+    fn title(&self) -> &'static str {
+        match self {
+            Check::Old => "Old dependencies",
+            Check::Deprecated => "Deprecated dependencies",
+        }
+    }
+}
+
+/// Package reported by a check, as described by formats which don't simply serialize it.
+pub trait Finding: 'static {
+    /// Headers of the columns describing the package in tables, after its name and installed version.
+    /// None by default, leaving only the name and installed version.
+    const COLUMNS: &'static [&'static str] = &[];
+
+    fn name(&self) -> &str;
+
+    fn local_version(&self) -> &str;
+
+    /// Sentence telling why the package is reported, e.g. how old it is.
+    fn description(&self) -> String;
+
+    /// Cells of the package under the [`COLUMNS`](Finding::COLUMNS).
+    fn cells(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Fields of the package in records (e.g. in CSV files) after its name and installed version, meant to be read
+    /// by programs rather than people: their name, and how to get their value. None by default.
+    const FIELDS: &'static [Field<Self>] = &[];
+}
+
+/// Field of a [`Finding`] in records: its name, and how to get its value from the package.
+pub type Field<T> = (&'static str, fn(&T) -> String);
+
+/// Results of `old` or `deprecated` for a single workspace, `T` holding the details of reported packages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WorkspaceResults<T> {
+    pub workspace: String,
+    /// Reported direct dependencies. Every checked category is present, even without reported packages.
+    pub direct: DepsByCategory<T>,
+    /// Reported transitive dependencies, along with the direct dependencies pulling them in.
+    /// Only `Some` in `--transitive` mode.
+    pub transitive: Option<Vec<(T, Vec<PkgName>)>>,
+    pub unmet_peers: Vec<UnmetPeerPkg>,
+    pub non_registry: Vec<NonRegistryPkg>,
+    pub unchecked: Vec<UncheckedPkg>,
+    /// Every checked direct dependency, reported or not.
+    pub checked: DepsByCategory<PkgNameAndVersion>,
+    /// Every checked transitive dependency, reported or not. Only `Some` in `--transitive` mode.
+    pub checked_transitive: Option<Vec<TransitivePkg>>,
+}
+
+/// Results of a workspace without any checked or reported package.
+///
+/// Not derived, which would require `T: Default`.
+impl<T> Default for WorkspaceResults<T> {
+    fn default() -> Self {
+        WorkspaceResults {
+            workspace: String::new(),
+            direct: DepsByCategory::new(),
+            transitive: None,
+            unmet_peers: vec![],
+            non_registry: vec![],
+            unchecked: vec![],
+            checked: DepsByCategory::new(),
+            checked_transitive: None,
+        }
+    }
+}
+
+impl<T> WorkspaceResults<T> {
+    /// Converts the details of every reported package, e.g. into a serializable form.
+    pub fn map<U>(self, f: impl Fn(T) -> U) -> WorkspaceResults<U> {
+        WorkspaceResults {
+            workspace: self.workspace,
+            direct: self
+                .direct
+                .into_iter()
+                .map(|(category, pkgs)| (category, pkgs.into_iter().map(&f).collect()))
+                .collect(),
+            transitive: self.transitive.map(|transitive| {
+                transitive
+                    .into_iter()
+                    .map(|(pkg, required_by)| (f(pkg), required_by))
+                    .collect()
+            }),
+            unmet_peers: self.unmet_peers,
+            non_registry: self.non_registry,
+            unchecked: self.unchecked,
+            checked: self.checked,
+            checked_transitive: self.checked_transitive,
+        }
+    }
+}
+
+/// Headers of the tables of reported packages, see [`table_rows`].
+fn table_header<T: Finding>() -> Vec<&'static str> {
+    ["Category", "Package", "Installed"]
+        .into_iter()
+        .chain(T::COLUMNS.iter().copied())
+        .collect()
+}
+
+/// Rows of the table of reported packages of a workspace: direct packages by category, then transitive packages
+/// along with the direct dependencies pulling them in.
+fn table_rows<T: Finding>(results: &WorkspaceResults<T>) -> Vec<Vec<String>> {
+    let direct = results
+        .direct
+        .iter()
+        .flat_map(|(category, pkgs)| pkgs.iter().map(|pkg| (category.as_str().to_owned(), pkg)));
+
+    let transitive = results
+        .transitive
+        .iter()
+        .flatten()
+        .map(|(pkg, required_by)| (format!("transitive ({})", required_by.join(", ")), pkg));
+
+    direct
+        .chain(transitive)
+        .map(|(category, pkg)| {
+            [
+                category,
+                pkg.name().to_owned(),
+                pkg.local_version().to_owned(),
+            ]
+            .into_iter()
+            .chain(pkg.cells())
+            .collect()
+        })
+        .collect()
+}
+
+/// Number of reported packages of a workspace in each category, e.g. `2 prod, 0 dev, 1 transitive`.
+///
+/// Every checked category is counted, and transitive packages only in `--transitive` mode.
+fn category_totals<T>(results: &WorkspaceResults<T>) -> String {
+    results
+        .direct
+        .iter()
+        .map(|(category, pkgs)| (category.as_str(), pkgs.len()))
+        .chain(
+            results
+                .transitive
+                .as_ref()
+                .map(|transitive| ("transitive", transitive.len())),
+        )
+        .map(|(category, num_pkgs)| format!("{num_pkgs} {category}"))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn num_reported<T>(results: &WorkspaceResults<T>) -> usize {
+    results.direct.values().map(Vec::len).sum::<usize>()
+        + results.transitive.as_ref().map_or(0, Vec::len)
+}
+
+/// Lists shown below the table of a workspace, with their title. Empty lists are left out.
+fn other_sections<T>(results: &WorkspaceResults<T>) -> Vec<(&'static str, Vec<String>)> {
+    let unmet_peers: Vec<String> = results
+        .unmet_peers
+        .iter()
+        .map(
+            |UnmetPeerPkg(name, range, installed)| match installed.as_slice() {
+                [] => format!("{name}@{range} (not installed)"),
+                _ => format!("{name}@{range} (installed: {})", installed.join(", ")),
+            },
+        )
+        .collect();
+
+    let non_registry: Vec<String> = results
+        .non_registry
+        .iter()
+        .map(|NonRegistryPkg(name, spec)| format!("{name} ({}: {spec})", spec.kind()))
+        .collect();
+
+    let unchecked: Vec<String> = results
+        .unchecked
+        .iter()
+        .map(|UncheckedPkg(PkgNameAndVersion(name, version), error)| {
+            format!("{name}@{version}: {}", error.reason())
+        })
+        .collect();
+
+    [
+        ("Unmet peer dependencies", unmet_peers),
+        ("Non-registry dependencies", non_registry),
+        ("Could not check", unchecked),
+    ]
+    .into_iter()
+    .filter(|(_, items)| !items.is_empty())
+    .collect()
+}
+
+/// Writes the results of every workspace in the given format.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **check:**      | Command whose results are written. |
+/// | **path:**       | Path to the root package.json, which workspaces are relative to. |
+/// | **results:**    | Results of each workspace. |
+/// | **write_text:** | Writes the text output of the command, which differs between commands. |
+pub fn write_report<T: Serialize + Finding, W: std::io::Write>(
+    format: Format,
+    check: Check,
+    path: &Path,
+    results: &[WorkspaceResults<T>],
+    mut writer: W,
+    write_text: impl FnOnce(&mut W) -> Result<(), std::io::Error>,
+) -> Result<(), std::io::Error> {
+    match format {
+        Format::Text => write_text(&mut writer),
+        Format::Json => json::write_json(check, results, writer),
+        Format::Sarif => sarif::write_sarif(check, path, results, writer),
+        Format::Markdown => markdown::write_markdown(check, results, writer),
+        Format::Html => html::write_html(check, results, writer),
+        Format::Csv => csv::write_csv(results, csv::Delimiter::Comma, writer),
+        Format::Tsv => csv::write_csv(results, csv::Delimiter::Tab, writer),
+        Format::Junit => junit::write_junit(check, results, writer),
+    }
+}
@@ -0,0 +1,452 @@
+//! [SARIF 2.1.0] output (`--format sarif`), read by code-scanning tools to annotate the package.json of workspaces.
+//!
+//! Each check is a rule (`old` and `deprecated`), and each reported package a result pointing at the line
+//! declaring the dependency in the workspace's package.json. Transitive packages point at the direct dependency
+//! pulling them in. Development dependencies are reported as warnings, other categories as errors.
+//!
+//! Packages that could not be checked are reported as notifications of the tool's execution.
+//!
+//! [SARIF 2.1.0]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use super::{Check, Finding, WorkspaceResults};
+use crate::package_json::DepSpec;
+use crate::types::{DepCategory, PkgName, PkgNameAndVersion, UncheckedPkg};
+
+static SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Rules of the tool, one per check, in the order of their `ruleIndex`.
+static RULES: [(Check, &str, &str); 2] = [
+    (
+        Check::Old,
+        "Old dependency",
+        "The installed version of the dependency was published longer ago than the age limit given to `--since`.",
+    ),
+    (
+        Check::Deprecated,
+        "Deprecated dependency",
+        "The installed version of the dependency is marked as deprecated in the registry.",
+    ),
+];
+
+/// Base of the URIs of package.json files: the folder of the root package.json.
+static SRCROOT: &str = "%SRCROOT%";
+
+/// Line and column (both starting at 1) of a dependency in a package.json.
+type Position = (usize, usize);
+
+pub fn write_sarif<T: Finding>(
+    check: Check,
+    path: &Path,
+    results: &[WorkspaceResults<T>],
+    mut writer: impl std::io::Write,
+) -> Result<(), std::io::Error> {
+    let rule_index = RULES
+        .iter()
+        .position(|(rule, ..)| *rule == check)
+        .expect("every check has a rule");
+
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|(rule, name, description)| {
+            json!({
+                "id": rule.id(),
+                "name": name,
+                "shortDescription": { "text": name },
+                "fullDescription": { "text": description },
+                "defaultConfiguration": { "level": "error" },
+            })
+        })
+        .collect();
+
+    let mut sarif_results = Vec::new();
+    let mut notifications = Vec::new();
+
+    for results in results {
+        let uri = package_json_uri(&results.workspace);
+
+        // without a readable package.json, results only point at the file
+        let positions = std::fs::read_to_string(path.join(&results.workspace).join("package.json"))
+            .map(|content| dependency_positions(&content))
+            .unwrap_or_default();
+
+        for (category, pkgs) in &results.direct {
+            for pkg in pkgs {
+                let position = positions.get(&(*category, pkg.name().to_owned()));
+
+                sarif_results.push(to_result(
+                    check,
+                    rule_index,
+                    level(*category),
+                    pkg.description(),
+                    &uri,
+                    position.copied(),
+                ));
+            }
+        }
+
+        for (pkg, required_by) in results.transitive.iter().flatten() {
+            // the direct dependencies pulling the package in, wherever they are declared
+            let declarations: Vec<(DepCategory, Position)> = required_by
+                .iter()
+                .flat_map(|name| {
+                    DepCategory::ALL.into_iter().filter_map(|category| {
+                        positions
+                            .get(&(category, name.clone()))
+                            .map(|position| (category, *position))
+                    })
+                })
+                .collect();
+
+            let is_development_only = !declarations.is_empty()
+                && declarations
+                    .iter()
+                    .all(|(category, _)| *category == DepCategory::Development);
+
+            sarif_results.push(to_result(
+                check,
+                rule_index,
+                if is_development_only {
+                    level(DepCategory::Development)
+                } else {
+                    level(DepCategory::Production)
+                },
+                format!(
+                    "{} (required by {})",
+                    pkg.description(),
+                    required_by.join(", ")
+                ),
+                &uri,
+                declarations.first().map(|(_, position)| *position),
+            ));
+        }
+
+        for UncheckedPkg(PkgNameAndVersion(name, version), error) in &results.unchecked {
+            notifications.push(json!({
+                "level": "warning",
+                "message": {
+                    "text": format!("[{}] {name}@{version} could not be checked: {}", results.workspace, error.reason())
+                },
+            }));
+        }
+    }
+
+    let sarif = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "debs",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "originalUriBaseIds": {
+                SRCROOT: { "uri": directory_uri(path) }
+            },
+            "invocations": [{
+                "executionSuccessful": true,
+                "toolExecutionNotifications": notifications,
+            }],
+            "results": sarif_results,
+        }]
+    });
+
+    serde_json::to_writer_pretty(&mut writer, &sarif)?;
+
+    writeln!(writer)
+}
+
+/// Development dependencies don't ship to production, so they are less of a concern.
+fn level(category: DepCategory) -> &'static str {
+    match category {
+        DepCategory::Development => "warning",
+        _ => "error",
+    }
+}
+
+fn to_result(
+    check: Check,
+    rule_index: usize,
+    level: &str,
+    message: String,
+    uri: &str,
+    position: Option<Position>,
+) -> Value {
+    let mut physical_location = json!({
+        "artifactLocation": { "uri": uri, "uriBaseId": SRCROOT },
+    });
+
+    if let Some((line, column)) = position {
+        physical_location["region"] = json!({ "startLine": line, "startColumn": column });
+    }
+
+    json!({
+        "ruleId": check.id(),
+        "ruleIndex": rule_index,
+        "level": level,
+        "message": { "text": message },
+        "locations": [{ "physicalLocation": physical_location }],
+    })
+}
+
+/// `./apps/web/` -> `apps/web/package.json`, relative to the root package.json.
+fn package_json_uri(workspace: &str) -> String {
+    workspace
+        .split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .chain(["package.json"])
+        .collect::<Vec<&str>>()
+        .join("/")
+}
+
+/// `/home/me/my project` -> `file:///home/me/my%20project/`, as SARIF expects base URIs to end with a slash.
+///
+/// Relative paths and `.` or `..` segments are resolved first, since tools resolve every artifact against this URI.
+fn directory_uri(path: &Path) -> String {
+    let path = std::fs::canonicalize(path)
+        .or_else(|_| std::path::absolute(path))
+        .unwrap_or_else(|_| path.to_owned());
+
+    let path = path.to_string_lossy().replace('\\', "/");
+
+    // Windows' canonical paths start with `//?/`, e.g. `//?/C:/project`
+    let path = path.trim_start_matches("//?/").trim_start_matches('/');
+
+    let encoded: String = path
+        .trim_end_matches('/')
+        .bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect();
+
+    format!("file:///{encoded}/")
+}
+
+/// Finds the position of every dependency declared in a package.json, by category and name.
+///
+/// Aliased dependencies (`"foo": "npm:bar@^2"`) are also found under the name of the aliased package,
+/// which is the name reported by the checks.
+///
+/// package.json files are expected to be formatted the way npm writes them, with a line per dependency.
+fn dependency_positions(content: &str) -> HashMap<(DepCategory, PkgName), Position> {
+    let mut positions = HashMap::new();
+    let mut current: Option<DepCategory> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let column = line.len() - trimmed.len() + 1;
+
+        let Some(category) = current else {
+            current = field_category(trimmed);
+            continue;
+        };
+
+        if trimmed.starts_with('}') || trimmed.starts_with(']') {
+            current = None;
+            continue;
+        }
+
+        let mut strings = trimmed.split('"').skip(1).step_by(2);
+
+        let Some(name) = strings.next() else {
+            continue;
+        };
+
+        positions.insert((category, name.to_owned()), (i + 1, column));
+
+        if let Some(DepSpec::Alias {
+            name: real_name, ..
+        }) = strings.next().map(DepSpec::parse)
+        {
+            positions
+                .entry((category, real_name))
+                .or_insert((i + 1, column));
+        }
+    }
+
+    positions
+}
+
+/// Category of the dependencies listed by the field opened on this line, e.g. `"devDependencies": {`.
+fn field_category(line: &str) -> Option<DepCategory> {
+    let (field, rest) = line.strip_prefix('"')?.split_once('"')?;
+
+    let rest = rest.trim_start().strip_prefix(':')?.trim();
+
+    // fields listing their dependencies on a single line (e.g. `{}`) are not looked through
+    if !(rest == "{" || rest == "[") {
+        return None;
+    }
+
+    match field {
+        "dependencies" => Some(DepCategory::Production),
+        "devDependencies" => Some(DepCategory::Development),
+        "optionalDependencies" => Some(DepCategory::Optional),
+        "peerDependencies" => Some(DepCategory::Peer),
+        "bundledDependencies" | "bundleDependencies" => Some(DepCategory::Bundled),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::error::DebsError;
+    use crate::types::DepsByCategory;
+
+    struct Details(&'static str);
+
+    impl Finding for Details {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn local_version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn description(&self) -> String {
+            format!("{}@1.0.0 is deprecated", self.0)
+        }
+    }
+
+    static PKG_JSON: &str = r#"{
+  "name": "specs",
+  "dependencies": {
+    "foo": "npm:bar@^2.0.0",
+    "ms": "^2.1.3"
+  },
+  "devDependencies": {
+    "debug": "^4.1.1"
+  },
+  "peerDependencies": {},
+  "bundledDependencies": [
+    "ms"
+  ]
+}"#;
+
+    #[test]
+    fn should_find_dependency_positions() {
+        let positions = dependency_positions(PKG_JSON);
+
+        let position = |category: DepCategory, name: &str| {
+            positions.get(&(category, name.to_owned())).copied()
+        };
+
+        assert_eq!(position(DepCategory::Production, "foo"), Some((4, 5)));
+        assert_eq!(position(DepCategory::Production, "bar"), Some((4, 5)));
+        assert_eq!(position(DepCategory::Production, "ms"), Some((5, 5)));
+        assert_eq!(position(DepCategory::Development, "debug"), Some((8, 5)));
+        assert_eq!(position(DepCategory::Bundled, "ms"), Some((12, 5)));
+        assert_eq!(position(DepCategory::Production, "name"), None);
+        assert_eq!(positions.len(), 5);
+    }
+
+    #[test]
+    fn should_point_results_at_dependencies() -> Result<(), std::io::Error> {
+        let results = [WorkspaceResults {
+            workspace: "./".to_owned(),
+            direct: DepsByCategory::from([
+                (DepCategory::Production, vec![Details("bar")]),
+                (DepCategory::Development, vec![Details("unknown")]),
+            ]),
+            transitive: Some(vec![(Details("ms"), vec!["foo".to_owned()])]),
+            unchecked: vec![UncheckedPkg(
+                PkgNameAndVersion("ms".to_owned(), "2.1.3".to_owned()),
+                Arc::new(DebsError::NotInCache("ms".to_owned())),
+            )],
+            ..Default::default()
+        }];
+
+        let mut bytes = Vec::new();
+
+        write_sarif(
+            Check::Deprecated,
+            Path::new("test-assets/specs"),
+            &results,
+            &mut bytes,
+        )?;
+
+        let sarif: Value = serde_json::from_slice(&bytes)?;
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"][1]["id"], "deprecated");
+
+        let results = run["results"].as_array().unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["ruleIndex"], 1);
+        assert_eq!(results[0]["level"], "error");
+        // `foo` is an alias of `bar`
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": { "uri": "package.json", "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": 7, "startColumn": 5 }
+            })
+        );
+        // not declared in package.json: only the file is known
+        assert_eq!(results[1]["level"], "warning");
+        assert!(results[1]["locations"][0]["physicalLocation"]["region"].is_null());
+        assert_eq!(
+            results[2]["message"]["text"],
+            "ms@1.0.0 is deprecated (required by foo)"
+        );
+        assert_eq!(
+            results[2]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            7
+        );
+        assert_eq!(
+            run["invocations"][0]["toolExecutionNotifications"][0]["message"]["text"],
+            "[./] ms@2.1.3 could not be checked: not found in the cache (offline)"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_locate_workspaces_package_json() {
+        assert_eq!(package_json_uri("./"), "package.json");
+        assert_eq!(package_json_uri("./apps/web/"), "apps/web/package.json");
+        assert_eq!(
+            directory_uri(Path::new("/home/me/project/")),
+            "file:///home/me/project/"
+        );
+        assert_eq!(
+            directory_uri(Path::new("/home/me/./my project#1/é")),
+            "file:///home/me/my%20project%231/%C3%A9/"
+        );
+    }
+
+    #[test]
+    fn should_resolve_relative_directories() -> Result<(), std::io::Error> {
+        let cwd = std::env::current_dir()?.canonicalize()?;
+
+        let expected = format!(
+            "file:///{}/test-assets/specs/",
+            cwd.to_string_lossy().trim_start_matches('/')
+        );
+
+        assert_eq!(directory_uri(Path::new("test-assets/specs")), expected);
+        assert_eq!(directory_uri(Path::new("./test-assets/specs/")), expected);
+        assert_eq!(
+            directory_uri(Path::new("test-assets/monorepo/../specs")),
+            expected
+        );
+
+        Ok(())
+    }
+}
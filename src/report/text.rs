@@ -0,0 +1,194 @@
+//! Sections of the text output shared by `old` and `deprecated`, which otherwise print their packages
+//! in their own way.
+
+use crate::package_json::NonRegistryPkg;
+use crate::types::{DepCategory, DepsByCategory, PkgNameAndVersion, UncheckedPkg, UnmetPeerPkg};
+
+/// Totals of reported packages, e.g. `1 old dependency, 0 old dev dependencies`,
+/// where `adjective` tells what is reported (e.g. `old`).
+///
+/// Each category of `pkgs` is counted, even without reported packages.
+/// Transitive packages are only counted in `--transitive` mode, when given.
+pub fn get_total<T>(
+    pkgs: &DepsByCategory<T>,
+    num_transitive: Option<usize>,
+    adjective: &str,
+) -> String {
+    let end = |count: usize| if count == 1 { "y" } else { "ies" };
+
+    let count = |category: DepCategory| pkgs.get(&category).map_or(0, Vec::len);
+
+    let num_prods = count(DepCategory::Production);
+
+    let mut total = if pkgs.contains_key(&DepCategory::Development) {
+        let num_devs = count(DepCategory::Development);
+
+        format!(
+            "{num_prods} {adjective} dependenc{}, {num_devs} {adjective} dev dependenc{}",
+            end(num_prods),
+            end(num_devs)
+        )
+    } else {
+        format!("{num_prods} {adjective} production dependencies")
+    };
+
+    for category in [
+        DepCategory::Optional,
+        DepCategory::Peer,
+        DepCategory::Bundled,
+    ] {
+        if pkgs.contains_key(&category) {
+            let num_pkgs = count(category);
+
+            total.push_str(&format!(
+                ", {num_pkgs} {adjective} {} dependenc{}",
+                category.as_str(),
+                end(num_pkgs)
+            ));
+        }
+    }
+
+    if let Some(num_transitive) = num_transitive {
+        total.push_str(&format!(
+            ", {num_transitive} {adjective} transitive dependenc{}",
+            end(num_transitive)
+        ));
+    }
+
+    total
+}
+
+/// Lists peer dependencies whose range no installed version satisfies, along with the installed versions.
+pub fn get_unmet_peers_output(pkgs: &[UnmetPeerPkg]) -> String {
+    if pkgs.is_empty() {
+        return String::new();
+    }
+
+    let mut res = "\n  unmet peer dependencies:\n\n".to_owned();
+
+    for UnmetPeerPkg(name, range, installed) in pkgs {
+        let installed = match installed.as_slice() {
+            [] => "not installed".to_owned(),
+            _ => format!("installed: {}", installed.join(", ")),
+        };
+
+        res.push_str(format!("    {name}@{range} ({installed})\n").as_str());
+    }
+
+    res
+}
+
+/// Lists dependencies which are not installed from the registry, along with their spec.
+pub fn get_non_registry_output(pkgs: &[NonRegistryPkg]) -> String {
+    if pkgs.is_empty() {
+        return String::new();
+    }
+
+    let mut res = "\n  non-registry dependencies:\n\n".to_owned();
+
+    for NonRegistryPkg(name, spec) in pkgs {
+        res.push_str(format!("    {name} ({}: {spec})\n", spec.kind()).as_str());
+    }
+
+    res
+}
+
+/// Lists packages that could not be checked, along with the reason why.
+pub fn get_unchecked_output(pkgs: &[UncheckedPkg]) -> String {
+    if pkgs.is_empty() {
+        return String::new();
+    }
+
+    let mut res = "\n  could not check:\n\n".to_owned();
+
+    for UncheckedPkg(PkgNameAndVersion(name, version), error) in pkgs {
+        res.push_str(format!("    {name}@{version}: {}\n", error.reason()).as_str());
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use reqwest::StatusCode;
+
+    use super::*;
+    use crate::error::DebsError;
+    use crate::package_json::DepSpec;
+
+    #[test]
+    fn should_count_reported_pkgs_by_category() {
+        let pkgs = DepsByCategory::from([
+            (DepCategory::Production, vec!["a"]),
+            (DepCategory::Development, vec!["b", "c"]),
+            (DepCategory::Bundled, vec![]),
+        ]);
+
+        assert_eq!(
+            get_total(&pkgs, None, "old"),
+            "1 old dependency, 2 old dev dependencies, 0 old bundled dependencies"
+        );
+        assert_eq!(
+            get_total(
+                &DepsByCategory::from([(DepCategory::Production, vec!["a"])]),
+                Some(1),
+                "deprecated"
+            ),
+            "1 deprecated production dependencies, 1 deprecated transitive dependency"
+        );
+    }
+
+    #[test]
+    fn should_list_unmet_peer_deps() {
+        assert_eq!(get_unmet_peers_output(&[]), "");
+
+        let output = get_unmet_peers_output(&[
+            UnmetPeerPkg(
+                "react".to_owned(),
+                "^17.0.0".to_owned(),
+                vec!["16.14.0".to_owned(), "18.2.0".to_owned()],
+            ),
+            UnmetPeerPkg("react-dom".to_owned(), "^17.0.0".to_owned(), vec![]),
+        ]);
+
+        assert_eq!(output, "\n  unmet peer dependencies:\n\n    react@^17.0.0 (installed: 16.14.0, 18.2.0)\n    react-dom@^17.0.0 (not installed)\n");
+    }
+
+    #[test]
+    fn should_list_non_registry_deps() {
+        assert_eq!(get_non_registry_output(&[]), "");
+
+        let output = get_non_registry_output(&[
+            NonRegistryPkg(
+                "common".to_owned(),
+                DepSpec::File("file:../common".to_owned()),
+            ),
+            NonRegistryPkg(
+                "debs".to_owned(),
+                DepSpec::Git("git+ssh://git@github.com/user/debs.git".to_owned()),
+            ),
+        ]);
+
+        assert_eq!(output, "\n  non-registry dependencies:\n\n    common (file: file:../common)\n    debs (git: git+ssh://git@github.com/user/debs.git)\n");
+    }
+
+    #[test]
+    fn should_list_unchecked_pkgs() {
+        assert_eq!(get_unchecked_output(&[]), "");
+
+        let output = get_unchecked_output(&[UncheckedPkg(
+            PkgNameAndVersion("a".to_owned(), "1.0.0".to_owned()),
+            Arc::new(DebsError::RegistryHttp {
+                pkg: "a".to_owned(),
+                status: StatusCode::NOT_FOUND,
+            }),
+        )]);
+
+        assert_eq!(
+            output,
+            "\n  could not check:\n\n    a@1.0.0: registry responded with 404 Not Found\n"
+        );
+    }
+}
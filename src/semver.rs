@@ -0,0 +1,513 @@
+//! Module reading versions and version ranges the way npm does (see [node-semver]).
+//!
+//! Ranges are sets of comparators separated by `||`, a version satisfying the range when it satisfies
+//! every comparator of one of the sets. Besides plain comparators (`>=1.2.3`, `<2.0.0`, `1.2.3`),
+//! npm accepts shorthands which are turned into plain comparators:
+//!
+//! | Range | Comparators |
+//! | ----- | ----------- |
+//! | `^1.2.3`          | `>=1.2.3 <2.0.0-0` |
+//! | `^0.2.3`          | `>=0.2.3 <0.3.0-0` |
+//! | `~1.2.3`          | `>=1.2.3 <1.3.0-0` |
+//! | `1.x`, `1`        | `>=1.0.0 <2.0.0-0` |
+//! | `*`, `x`, `""`    | `>=0.0.0` |
+//! | `1.2.3 - 2.3`     | `>=1.2.3 <2.4.0-0` |
+//!
+//! Pre-release versions (e.g. `1.2.3-beta.1`) only satisfy a set of comparators when one of them
+//! is a pre-release of the same `major.minor.patch`, so that `^1.2.3` doesn't pull in `1.3.0-beta`.
+//!
+//! [node-semver]: https://github.com/npm/node-semver#ranges
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A `major.minor.patch[-prerelease][+build]` version. Build metadata is ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    pub pre: Vec<PreIdentifier>,
+}
+
+/// Dot-separated identifier of a pre-release, e.g. `beta` and `1` in `1.2.3-beta.1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PreIdentifier {
+    Numeric(u64),
+    AlphaNumeric(String),
+}
+
+impl SemVer {
+    /// Parses a version such as `1.2.3`, `v1.2.3` or `1.2.3-beta.1+build.5`.
+    ///
+    /// `None` for anything else, e.g. partial versions (`1.2`) or tags (`latest`).
+    pub fn parse(version: &str) -> Option<SemVer> {
+        match Partial::parse(version)? {
+            Partial {
+                major: Some(major),
+                minor: Some(minor),
+                patch: Some(patch),
+                pre,
+            } => Some(SemVer {
+                major,
+                minor,
+                patch,
+                pre,
+            }),
+            _ => None,
+        }
+    }
+
+    fn new(major: u64, minor: u64, patch: u64) -> Self {
+        SemVer {
+            major,
+            minor,
+            patch,
+            pre: vec![],
+        }
+    }
+
+    /// Smallest pre-release of this version: `2.0.0` -> `2.0.0-0`, to exclude every pre-release of upper bounds.
+    fn lowest_pre(mut self) -> Self {
+        self.pre = vec![PreIdentifier::Numeric(0)];
+        self
+    }
+
+    /// `2.0.0-0` -> `2.0.0`
+    fn release(mut self) -> Self {
+        self.pre.clear();
+        self
+    }
+
+    fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    fn same_release(&self, other: &SemVer) -> bool {
+        (self.major, self.minor, self.patch) == (other.major, other.minor, other.patch)
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                // a pre-release comes before its release
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => self.pre.cmp(&other.pre),
+            })
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PreIdentifier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (PreIdentifier::Numeric(a), PreIdentifier::Numeric(b)) => a.cmp(b),
+            (PreIdentifier::AlphaNumeric(a), PreIdentifier::AlphaNumeric(b)) => a.cmp(b),
+            // numeric identifiers always have lower precedence
+            (PreIdentifier::Numeric(_), PreIdentifier::AlphaNumeric(_)) => Ordering::Less,
+            (PreIdentifier::AlphaNumeric(_), PreIdentifier::Numeric(_)) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for PreIdentifier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+
+        for (i, identifier) in self.pre.iter().enumerate() {
+            let separator = if i == 0 { '-' } else { '.' };
+
+            match identifier {
+                PreIdentifier::Numeric(n) => write!(f, "{separator}{n}")?,
+                PreIdentifier::AlphaNumeric(s) => write!(f, "{separator}{s}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Version whose parts may be missing or wildcards (`x`, `X`, `*`), e.g. `1.2`, `1.x` or `*`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Partial {
+    major: Option<u64>,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: Vec<PreIdentifier>,
+}
+
+impl Partial {
+    fn parse(version: &str) -> Option<Partial> {
+        let version = version.trim();
+        let version = version.strip_prefix('=').unwrap_or(version).trim_start();
+        let version = version
+            .strip_prefix('v')
+            .or_else(|| version.strip_prefix('V'))
+            .unwrap_or(version);
+
+        let version = match version.split_once('+') {
+            Some((version, _build)) => version,
+            None => version,
+        };
+
+        let (release, pre) = match version.split_once('-') {
+            Some((release, pre)) => (release, Some(pre)),
+            None => (version, None),
+        };
+
+        let mut parts = Vec::new();
+
+        if !release.is_empty() {
+            for part in release.split('.') {
+                parts.push(match part {
+                    "x" | "X" | "*" => None,
+                    _ if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) => {
+                        Some(part.parse().ok()?)
+                    }
+                    _ => return None,
+                });
+            }
+        }
+
+        if parts.len() > 3 {
+            return None;
+        }
+
+        parts.resize(3, None);
+
+        // `1.x.3` is `1.x`
+        if let Some(i) = parts.iter().position(Option::is_none) {
+            parts[i..].fill(None);
+        }
+
+        let pre = match pre {
+            Some(pre) => pre
+                .split('.')
+                .map(|identifier| match identifier {
+                    "" => None,
+                    _ if identifier.bytes().all(|b| b.is_ascii_digit()) => {
+                        identifier.parse().ok().map(PreIdentifier::Numeric)
+                    }
+                    _ if identifier
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'-') =>
+                    {
+                        Some(PreIdentifier::AlphaNumeric(identifier.to_owned()))
+                    }
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?,
+            None => vec![],
+        };
+
+        Some(Partial {
+            major: parts[0],
+            minor: parts[1],
+            patch: parts[2],
+            pre,
+        })
+    }
+
+    /// Lowest version matching this partial: `1.x` -> `1.0.0`.
+    fn floor(&self) -> SemVer {
+        SemVer {
+            major: self.major.unwrap_or(0),
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+        }
+    }
+
+    /// First version no longer matching this partial: `1.x` -> `2.0.0-0`, `1.2` -> `1.3.0-0`.
+    ///
+    /// `None` for `*`, and for full versions which only match themselves.
+    fn ceiling(&self) -> Option<SemVer> {
+        match (self.major, self.minor, self.patch) {
+            (Some(major), None, _) => Some(SemVer::new(major + 1, 0, 0).lowest_pre()),
+            (Some(major), Some(minor), None) => Some(SemVer::new(major, minor + 1, 0).lowest_pre()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Eq,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Comparator(Operator, SemVer);
+
+impl Comparator {
+    fn matches(&self, version: &SemVer) -> bool {
+        let Comparator(operator, bound) = self;
+
+        match operator {
+            Operator::Lt => version < bound,
+            Operator::Lte => version <= bound,
+            Operator::Gt => version > bound,
+            Operator::Gte => version >= bound,
+            Operator::Eq => version == bound,
+        }
+    }
+}
+
+/// A version range, such as `^1.2.3 || >=2.0.0 <3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Range(Vec<Vec<Comparator>>);
+
+impl Range {
+    /// Parses a range, see the [module documentation](self) for the accepted syntax.
+    ///
+    /// `None` for specs which are not ranges, e.g. tags (`latest`), URLs or paths.
+    pub fn parse(range: &str) -> Option<Range> {
+        range
+            .split("||")
+            .map(parse_comparator_set)
+            .collect::<Option<Vec<_>>>()
+            .map(Range)
+    }
+
+    /// Whether `version` is in the range.
+    pub fn satisfies(&self, version: &SemVer) -> bool {
+        self.0.iter().any(|set| {
+            set.iter().all(|comparator| comparator.matches(version))
+                && (!version.is_prerelease()
+                    || set.iter().any(|Comparator(_, bound)| {
+                        bound.is_prerelease() && bound.same_release(version)
+                    }))
+        })
+    }
+}
+
+/// `>=1.2.3 <2` -> `[>=1.2.3, <2.0.0-0]`, `1.2 - 2` -> `[>=1.2.0, <3.0.0-0]`
+fn parse_comparator_set(set: &str) -> Option<Vec<Comparator>> {
+    let tokens = tokenize(set);
+
+    match tokens.as_slice() {
+        [from, dash, to] if dash == "-" => {
+            let from = Partial::parse(from)?;
+            let to = Partial::parse(to)?;
+
+            let mut comparators = vec![Comparator(Operator::Gte, from.floor())];
+
+            match (to.major.is_none(), to.ceiling()) {
+                // `1.2.3 - *`: no upper bound
+                (true, _) => {}
+                (false, Some(ceiling)) => comparators.push(Comparator(Operator::Lt, ceiling)),
+                (false, None) => comparators.push(Comparator(Operator::Lte, to.floor())),
+            }
+
+            Some(comparators)
+        }
+        _ => tokens
+            .iter()
+            .map(|token| desugar(token))
+            .collect::<Option<Vec<_>>>()
+            .map(|sets| sets.into_iter().flatten().collect()),
+    }
+}
+
+/// Splits a set of comparators on whitespace, keeping operators with their version (`>= 1.2.3` -> `>=1.2.3`).
+fn tokenize(set: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+
+    for word in set.split_whitespace() {
+        match tokens.last_mut() {
+            Some(last) if is_operator(last) => last.push_str(word),
+            _ => tokens.push(word.to_owned()),
+        }
+    }
+
+    tokens
+}
+
+fn is_operator(token: &str) -> bool {
+    matches!(token, "<" | "<=" | ">" | ">=" | "=" | "^" | "~" | "~>")
+}
+
+/// Turns a single comparator, possibly a shorthand, into plain comparators.
+fn desugar(token: &str) -> Option<Vec<Comparator>> {
+    let (operator, version) = split_operator(token);
+
+    let partial = Partial::parse(version)?;
+
+    let floor = partial.floor();
+
+    let comparators = match operator {
+        "^" => {
+            let ceiling = match (partial.major, partial.minor, partial.patch) {
+                (None, ..) => None,
+                (Some(0), Some(0), Some(patch)) => Some(SemVer::new(0, 0, patch + 1)),
+                (Some(0), Some(minor), _) => Some(SemVer::new(0, minor + 1, 0)),
+                (Some(major), ..) => Some(SemVer::new(major + 1, 0, 0)),
+            };
+
+            with_ceiling(floor, ceiling.map(SemVer::lowest_pre))
+        }
+        "~" | "~>" => {
+            let ceiling = match (partial.major, partial.minor) {
+                (None, _) => None,
+                (Some(major), None) => Some(SemVer::new(major + 1, 0, 0)),
+                (Some(major), Some(minor)) => Some(SemVer::new(major, minor + 1, 0)),
+            };
+
+            with_ceiling(floor, ceiling.map(SemVer::lowest_pre))
+        }
+        "" | "=" => match partial.ceiling() {
+            _ if partial.major.is_none() => vec![Comparator(Operator::Gte, floor)],
+            Some(ceiling) => with_ceiling(floor, Some(ceiling)),
+            None => vec![Comparator(Operator::Eq, floor)],
+        },
+        ">" => match partial.ceiling() {
+            // `>*` matches nothing
+            _ if partial.major.is_none() => vec![Comparator(Operator::Lt, SemVer::new(0, 0, 0))],
+            // `>1.2` is `>=1.3.0`
+            Some(ceiling) => vec![Comparator(Operator::Gte, ceiling.release())],
+            None => vec![Comparator(Operator::Gt, floor)],
+        },
+        ">=" => vec![Comparator(Operator::Gte, floor)],
+        "<" => match partial.major {
+            // `<*` matches nothing
+            None => vec![Comparator(Operator::Lt, SemVer::new(0, 0, 0))],
+            // `<1.2` is `<1.2.0-0`
+            Some(_) if partial.patch.is_none() => {
+                vec![Comparator(Operator::Lt, floor.lowest_pre())]
+            }
+            Some(_) => vec![Comparator(Operator::Lt, floor)],
+        },
+        "<=" => match partial.ceiling() {
+            _ if partial.major.is_none() => vec![Comparator(Operator::Gte, floor)],
+            // `<=1.2` is `<1.3.0-0`
+            Some(ceiling) => vec![Comparator(Operator::Lt, ceiling)],
+            None => vec![Comparator(Operator::Lte, floor)],
+        },
+        _ => return None,
+    };
+
+    Some(comparators)
+}
+
+fn with_ceiling(floor: SemVer, ceiling: Option<SemVer>) -> Vec<Comparator> {
+    let mut comparators = vec![Comparator(Operator::Gte, floor)];
+
+    if let Some(ceiling) = ceiling {
+        comparators.push(Comparator(Operator::Lt, ceiling));
+    }
+
+    comparators
+}
+
+/// `>=1.2.3` -> `(">=", "1.2.3")`, `1.2.3` -> `("", "1.2.3")`
+fn split_operator(token: &str) -> (&str, &str) {
+    let end = token
+        .find(|c: char| !matches!(c, '<' | '>' | '=' | '^' | '~'))
+        .unwrap_or(token.len());
+
+    (&token[..end], &token[end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn satisfies(range: &str, version: &str) -> bool {
+        Range::parse(range)
+            .expect("valid range")
+            .satisfies(&SemVer::parse(version).expect("valid version"))
+    }
+
+    #[test]
+    fn should_parse_versions() {
+        assert_eq!(SemVer::parse("v1.2.3"), Some(SemVer::new(1, 2, 3)));
+        assert_eq!(
+            SemVer::parse("1.2.3-beta.1+build.5").map(|v| v.to_string()),
+            Some("1.2.3-beta.1".to_owned())
+        );
+        assert_eq!(SemVer::parse("1.2"), None);
+        assert_eq!(SemVer::parse("latest"), None);
+    }
+
+    #[test]
+    fn should_order_prereleases_before_releases() {
+        let parse = |version: &str| SemVer::parse(version).unwrap();
+
+        assert!(parse("1.0.0-alpha") < parse("1.0.0-alpha.1"));
+        assert!(parse("1.0.0-alpha.1") < parse("1.0.0-alpha.beta"));
+        assert!(parse("1.0.0-beta.2") < parse("1.0.0-beta.11"));
+        assert!(parse("1.0.0-rc.1") < parse("1.0.0"));
+    }
+
+    #[test]
+    fn should_satisfy_caret_and_tilde_ranges() {
+        assert!(satisfies("^1.2.3", "1.9.0"));
+        assert!(!satisfies("^1.2.3", "2.0.0"));
+        assert!(!satisfies("^1.2.3", "1.2.2"));
+        assert!(satisfies("^0.2.3", "0.2.9"));
+        assert!(!satisfies("^0.2.3", "0.3.0"));
+        assert!(!satisfies("^0.0.3", "0.0.4"));
+        assert!(satisfies("^1.x", "1.5.0"));
+        assert!(satisfies("~1.2.3", "1.2.9"));
+        assert!(!satisfies("~1.2.3", "1.3.0"));
+        assert!(satisfies("~1", "1.9.9"));
+    }
+
+    #[test]
+    fn should_satisfy_x_and_hyphen_ranges() {
+        assert!(satisfies("*", "3.0.0"));
+        assert!(satisfies("", "3.0.0"));
+        assert!(satisfies("1.x", "1.4.0"));
+        assert!(!satisfies("1.2.x", "1.3.0"));
+        assert!(satisfies("1.2.3 - 2.3", "2.3.9"));
+        assert!(!satisfies("1.2.3 - 2.3.4", "2.3.5"));
+        assert!(!satisfies("1.2.3 - 2.3.4", "1.2.2"));
+    }
+
+    #[test]
+    fn should_satisfy_comparators_and_unions() {
+        assert!(satisfies(">=1.2.3 <2", "1.9.0"));
+        assert!(satisfies(">= 1.2.3 < 2", "1.9.0"));
+        assert!(!satisfies(">=1.2.3 <2", "2.0.0"));
+        assert!(satisfies(">1.2", "1.3.0"));
+        assert!(!satisfies(">1.2", "1.2.9"));
+        assert!(satisfies("<=1.2", "1.2.9"));
+        assert!(satisfies("^16.8.0 || ^17.0.0 || ^18.0.0", "18.2.0"));
+        assert!(!satisfies("^16.8.0 || ^17.0.0", "18.2.0"));
+        assert!(satisfies("=1.2.3", "1.2.3"));
+    }
+
+    #[test]
+    fn should_only_satisfy_prereleases_of_the_same_release() {
+        assert!(satisfies(">=1.2.3-beta.1", "1.2.3-beta.2"));
+        assert!(!satisfies(">=1.2.3-beta.1", "1.3.0-beta.1"));
+        assert!(!satisfies("^1.2.3", "1.3.0-beta"));
+        assert!(!satisfies("<2", "2.0.0-beta"));
+    }
+
+    #[test]
+    fn should_reject_specs_that_are_not_ranges() {
+        assert_eq!(Range::parse("latest"), None);
+        assert_eq!(Range::parse("file:../common"), None);
+        assert_eq!(Range::parse("1.2.3.4"), None);
+    }
+}
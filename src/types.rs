@@ -1,6 +1,91 @@
-#[derive(Debug, PartialEq, Eq)]
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::error::DebsError;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct PkgNameAndVersion(pub PkgName, pub Version);
 
 pub type PkgName = String;
 
 pub type Version = String;
+
+/// A package whose registry data could not be checked, along with the error met.
+///
+/// The error is shared, as the same package may be used by several workspaces.
+#[derive(Clone, Debug)]
+pub struct UncheckedPkg(pub PkgNameAndVersion, pub Arc<DebsError>);
+
+/// Errors are told apart by their description, as most of the errors they wrap can't be compared.
+impl PartialEq for UncheckedPkg {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0 && self.1.to_string() == other.1.to_string()
+    }
+}
+
+impl Eq for UncheckedPkg {}
+
+/// A package installed as a dependency of dependencies, along with the direct dependencies pulling it in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransitivePkg(pub PkgNameAndVersion, pub Vec<PkgName>);
+
+/// Kind of direct dependency, after the package.json field listing it.
+///
+/// The order of the variants is the order in which categories are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum DepCategory {
+    /// `dependencies`
+    Production,
+    /// `devDependencies`
+    Development,
+    /// `optionalDependencies`, which npm also installs in production
+    Optional,
+    /// `peerDependencies`, which the workspace expects the project to install
+    Peer,
+    /// `bundledDependencies` (or `bundleDependencies`), shipped inside the package itself
+    Bundled,
+}
+
+impl DepCategory {
+    pub const ALL: [DepCategory; 5] = [
+        DepCategory::Production,
+        DepCategory::Development,
+        DepCategory::Optional,
+        DepCategory::Peer,
+        DepCategory::Bundled,
+    ];
+
+    /// Name of the category, as given to `--omit` and `--include`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DepCategory::Production => "prod",
+            DepCategory::Development => "dev",
+            DepCategory::Optional => "optional",
+            DepCategory::Peer => "peer",
+            DepCategory::Bundled => "bundled",
+        }
+    }
+
+    /// Header of the category's section in the output of `old` and `deprecated`.
+    pub fn header(&self) -> &'static str {
+        match self {
+            DepCategory::Production => "production:",
+            DepCategory::Development => "development:",
+            DepCategory::Optional => "optional:",
+            DepCategory::Peer => "peer:",
+            DepCategory::Bundled => "bundled:",
+        }
+    }
+}
+
+/// Direct dependencies of a workspace, by category.
+pub type DepsByCategory<T> = BTreeMap<DepCategory, Vec<T>>;
+
+/// Peer dependency whose range no installed version satisfies: name, range, and the installed versions (if any).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnmetPeerPkg(pub PkgName, pub String, pub Vec<Version>);
+
+/// Direct dependency whose installed version doesn't satisfy the range declared in package.json:
+/// name, range, and the installed version (`None` when not installed).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StaleLockPkg(pub PkgName, pub String, pub Option<Version>);
@@ -0,0 +1,104 @@
+//! Module checking that the lockfile is in sync with the package.json of each workspace.
+//!
+//! The entry point to this module is [get_verify_lock_output].
+//!
+//! Lockfiles are written by package managers, but can go stale when a package.json is edited without
+//! installing again, or be edited by hand when resolving conflicts. We check that the installed version
+//! of each direct dependency still satisfies the range declared in package.json (see [crate::semver]).
+
+use std::path::Path;
+
+use crate::error::DebsError;
+use crate::package_json::{get_stale_lock_deps, Lockfile};
+use crate::types::{DepCategory, DepsByCategory, StaleLockPkg};
+
+/// Takes workspace paths and returns a string listing, for each of them, the direct dependencies
+/// whose installed version doesn't satisfy the declared range, along with their total number.
+///
+/// Fails when the package.json of a workspace cannot be read.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **path:**       | Path to the root package.json containing workspace names. |
+/// | **workspaces:** | Workspaces to check. |
+/// | **lock:**       | Root lockfile, listing the installed versions. |
+/// | **categories:** | Categories of dependencies to check. |
+pub fn get_verify_lock_output(
+    path: &Path,
+    workspaces: &[String],
+    lock: &Lockfile,
+    categories: &[DepCategory],
+) -> Result<(String, usize), DebsError> {
+    workspaces
+        .iter()
+        .try_fold((String::new(), 0), |(acc, total), workspace| {
+            let stale = get_stale_lock_deps(path, workspace, lock, categories)?;
+
+            let num_stale = stale.values().map(Vec::len).sum::<usize>();
+
+            let output = get_output(&stale);
+
+            Ok((
+                format!("{acc}\n[{workspace}] ranges not satisfied by the lockfile:\n{output}"),
+                total + num_stale,
+            ))
+        })
+}
+
+fn get_output(stale: &DepsByCategory<StaleLockPkg>) -> String {
+    let num_stale = stale.values().map(Vec::len).sum::<usize>();
+
+    let res = stale.iter().fold(String::new(), |acc, (category, pkgs)| {
+        let lines = pkgs
+            .iter()
+            .fold(String::new(), |acc, StaleLockPkg(name, range, version)| {
+                let installed = match version {
+                    Some(version) => format!("installed: {version}"),
+                    None => "not installed".to_owned(),
+                };
+
+                format!("{acc}    {name}@{range} ({installed})\n")
+            });
+
+        format!("{acc}\n  {}\n\n{lines}", category.header())
+    });
+
+    format!(
+        "{res}\n  total: {num_stale} unsatisfied range{end}\n",
+        end = if num_stale == 1 { "" } else { "s" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_return_output_for_stale_deps() {
+        let stale = DepsByCategory::from([
+            (
+                DepCategory::Production,
+                vec![StaleLockPkg(
+                    "express".to_owned(),
+                    "^4.18.0".to_owned(),
+                    Some("4.16.4".to_owned()),
+                )],
+            ),
+            (
+                DepCategory::Development,
+                vec![StaleLockPkg("debug".to_owned(), "^4.1.0".to_owned(), None)],
+            ),
+        ]);
+
+        assert_eq!(
+            get_output(&stale),
+            "\n  production:\n\n    express@^4.18.0 (installed: 4.16.4)\n\n  development:\n\n    debug@^4.1.0 (not installed)\n\n  total: 2 unsatisfied ranges\n"
+        );
+        assert_eq!(
+            get_output(&DepsByCategory::new()),
+            "\n  total: 0 unsatisfied ranges\n"
+        );
+    }
+}
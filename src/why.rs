@@ -0,0 +1,127 @@
+//! Module explaining why a package is installed.
+//!
+//! The entry point to this module is [get_why_output].
+//!
+//! Packages are looked up in the lockfile (e.g. package-lock.json), which lists every installed package
+//! along with its dependencies. Starting from the dependencies of each workspace, we print every chain
+//! of dependencies leading to the package, e.g. `express@4.18.2 > serve-static@1.15.0`.
+
+use std::path::Path;
+
+use crate::error::DebsError;
+use crate::package_json::{get_dependency_paths, Lockfile};
+use crate::types::{DepCategory, PkgNameAndVersion};
+
+/// Takes workspace paths and returns a string listing, for each of them,
+/// the chains of dependencies leading to the package described by `query`.
+///
+/// Fails when the package.json of a workspace cannot be read.
+///
+/// ## Parameters
+///
+/// | Parameter | Description |
+/// | --------- | ----------- |
+/// | **path:**       | Path to the root package.json containing workspace names. |
+/// | **workspaces:** | Workspaces to start looking from. |
+/// | **lock:**       | Root lockfile, listing the dependencies of each package. |
+/// | **query:**      | Package to look for, as `<name>` or `<name>@<version>`. |
+/// | **categories:** | Categories of direct dependencies to start looking from. |
+pub fn get_why_output(
+    path: &Path,
+    workspaces: &[String],
+    lock: &Lockfile,
+    query: &str,
+    categories: &[DepCategory],
+) -> Result<String, DebsError> {
+    let (pkg_name, version) = parse_query(query);
+
+    workspaces.iter().try_fold(String::new(), |acc, workspace| {
+        let dependency_paths =
+            get_dependency_paths(path, workspace, lock, pkg_name, version, categories)?;
+
+        let output = get_output(&dependency_paths);
+
+        Ok(format!("{acc}\n[{workspace}] paths to {query}:\n{output}"))
+    })
+}
+
+/// `@babel/core@7.0.0` -> `("@babel/core", Some("7.0.0"))`, `@babel/core` -> `("@babel/core", None)`
+fn parse_query(query: &str) -> (&str, Option<&str>) {
+    // the `@` of scoped packages is not a version separator
+    match query.rfind('@') {
+        Some(i) if i > 0 => (&query[..i], Some(&query[i + 1..])),
+        _ => (query, None),
+    }
+}
+
+fn get_output(dependency_paths: &[Vec<PkgNameAndVersion>]) -> String {
+    let num_paths = dependency_paths.len();
+
+    let res = dependency_paths.iter().fold(String::new(), |acc, chain| {
+        let chain = chain
+            .iter()
+            .map(|PkgNameAndVersion(name, version)| match version.as_str() {
+                // workspaces don't need a version
+                "" => name.clone(),
+                _ => format!("{name}@{version}"),
+            })
+            .collect::<Vec<String>>()
+            .join(" > ");
+
+        format!("{acc}\n    {chain}")
+    });
+
+    format!(
+        "{res}{separator}\n  total: {num_paths} path{end}\n",
+        separator = if num_paths == 0 { "" } else { "\n" },
+        end = if num_paths == 1 { "" } else { "s" },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package_json::parse_lockfile;
+
+    #[test]
+    fn should_parse_queries() {
+        assert_eq!(parse_query("express"), ("express", None));
+        assert_eq!(parse_query("express@4.18.2"), ("express", Some("4.18.2")));
+        assert_eq!(parse_query("@babel/core"), ("@babel/core", None));
+        assert_eq!(
+            parse_query("@babel/core@7.0.0"),
+            ("@babel/core", Some("7.0.0"))
+        );
+    }
+
+    #[test]
+    fn should_return_output_for_dependency_paths() {
+        let chain = vec![
+            PkgNameAndVersion("common".to_owned(), "".to_owned()),
+            PkgNameAndVersion("express".to_owned(), "4.18.2".to_owned()),
+            PkgNameAndVersion("serve-static".to_owned(), "1.15.0".to_owned()),
+        ];
+
+        assert_eq!(
+            get_output(&[chain]),
+            "\n    common > express@4.18.2 > serve-static@1.15.0\n\n  total: 1 path\n"
+        );
+        assert_eq!(get_output(&[]), "\n  total: 0 paths\n");
+    }
+
+    #[test]
+    fn should_explain_monorepo_package() -> Result<(), DebsError> {
+        let path = Path::new("./test-assets/monorepo/");
+
+        let workspaces = ["backend/".to_owned()];
+
+        let lock = parse_lockfile(path, &workspaces)?;
+
+        let output = get_why_output(path, &workspaces, &lock, "serve-static", &DepCategory::ALL)?;
+
+        assert!(output.starts_with("\n[backend/] paths to serve-static:\n"));
+        assert!(output.contains("\n    express@4.18.2 > serve-static@1.15.0\n"));
+
+        Ok(())
+    }
+}
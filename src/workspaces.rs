@@ -0,0 +1,273 @@
+//! Module finding the workspaces of a project on disk.
+//!
+//! The `workspaces` field of the root package.json lists paths to the workspaces, or globs matching them:
+//!
+//! ``` json
+//! "workspaces": [
+//!     "common/",
+//!     "packages/*",
+//!     "apps/**",
+//!     "!apps/legacy"
+//! ]
+//! ```
+//!
+//! Like npm, a glob matches every folder containing a package.json (outside of `node_modules/`),
+//! where `*` matches any part of a folder name and `**` any number of nested folders.
+//! Entries starting with `!` exclude the folders they match.
+//!
+//! pnpm lists them in the `packages` field of `pnpm-workspace.yaml` instead, with the same syntax.
+//!
+//! Projects without workspaces are checked as a single workspace: the root package itself.
+
+use std::path::Path;
+
+use crate::error::DebsError;
+use crate::package_json::{pnpm_workspace_patterns, PackageJson};
+
+/// Workspace standing for the root package, in projects without workspaces.
+pub static ROOT_WORKSPACE: &str = "./";
+
+/// Returns the paths to the workspaces of the project at `root` (relative to it),
+/// followed by the entries of the `workspaces` field matching no workspace on disk.
+///
+/// Paths listed as-is are returned as written, while folders matched by globs end with a `/`,
+/// e.g. `packages/a/`. Each workspace is returned once, in the order of the `workspaces` field.
+///
+/// Fails when `pnpm-workspace.yaml` exists but cannot be read, or when none of the listed workspaces exist.
+pub fn discover_workspaces(
+    root: &Path,
+    pkg_json: &PackageJson,
+) -> Result<(Vec<String>, Vec<String>), DebsError> {
+    let pnpm_patterns;
+
+    let (patterns, listed_in) = match pkg_json.workspace_patterns() {
+        Some(patterns) => (patterns, "package.json"),
+        None => match pnpm_workspace_patterns(root)? {
+            Some(patterns) => {
+                pnpm_patterns = patterns;
+                (pnpm_patterns.as_slice(), "pnpm-workspace.yaml")
+            }
+            None => return Ok((vec![ROOT_WORKSPACE.to_owned()], vec![])),
+        },
+    };
+
+    // only listed when a glob needs them
+    let mut folders: Option<Vec<String>> = None;
+
+    let mut workspaces: Vec<String> = Vec::new();
+    let mut missing = Vec::new();
+
+    for pattern in patterns {
+        if let Some(excluded) = pattern.strip_prefix('!') {
+            let excluded = segments(excluded);
+            workspaces.retain(|workspace| !glob_match(&excluded, &segments(workspace)));
+            continue;
+        }
+
+        let matches: Vec<String> = if is_glob(pattern) {
+            let pattern_segments = segments(pattern);
+
+            folders
+                .get_or_insert_with(|| package_folders(root))
+                .iter()
+                .filter(|folder| glob_match(&pattern_segments, &segments(folder)))
+                .map(|folder| format!("{folder}/"))
+                .collect()
+        } else if root.join(pattern).join("package.json").is_file() {
+            vec![pattern.clone()]
+        } else {
+            vec![]
+        };
+
+        if matches.is_empty() {
+            missing.push(pattern.clone());
+        }
+
+        for workspace in matches {
+            // `common` and `common/` are the same workspace
+            if !workspaces
+                .iter()
+                .any(|known| segments(known) == segments(&workspace))
+            {
+                workspaces.push(workspace);
+            }
+        }
+    }
+
+    if workspaces.is_empty() {
+        return Err(DebsError::MissingWorkspaces(root.join(listed_in)));
+    }
+
+    Ok((workspaces, missing))
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+/// `./packages/a/` -> `["packages", "a"]`
+fn segments(path: &str) -> Vec<&str> {
+    path.split(['/', '\\'])
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+        .collect()
+}
+
+/// Folders under `root` containing a package.json, relative to `root` and sorted.
+///
+/// `node_modules/` and hidden folders (e.g. `.git/`) are not visited.
+fn package_folders(root: &Path) -> Vec<String> {
+    fn visit(root: &Path, relative: &str, folders: &mut Vec<String>) {
+        let entries = match std::fs::read_dir(root.join(relative)) {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if name == "node_modules" || name.starts_with('.') {
+                continue;
+            }
+
+            // `file_type` doesn't follow symlinks, which avoids visiting cycles
+            if !entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                continue;
+            }
+
+            let folder = if relative.is_empty() {
+                name
+            } else {
+                format!("{relative}/{name}")
+            };
+
+            if entry.path().join("package.json").is_file() {
+                folders.push(folder.clone());
+            }
+
+            visit(root, &folder, folders);
+        }
+    }
+
+    let mut folders = Vec::new();
+
+    visit(root, "", &mut folders);
+
+    folders.sort_unstable();
+
+    folders
+}
+
+/// Matches path segments against glob segments, where `**` matches any number of segments.
+fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skipped| glob_match(rest, &path[skipped..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => segment_match(segment, name) && glob_match(rest, path_rest),
+            None => false,
+        },
+    }
+}
+
+/// Matches a single folder name, where `*` matches any characters and `?` a single one.
+fn segment_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|skipped| matches(rest, &name[skipped..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    matches(&pattern, &name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::package_json::parse_package_json;
+
+    use super::*;
+
+    #[test]
+    fn should_match_globs() {
+        assert!(glob_match(&["packages", "*"], &["packages", "a"]));
+        assert!(!glob_match(&["packages", "*"], &["packages", "a", "b"]));
+        assert!(glob_match(&["apps", "**"], &["apps", "a", "b"]));
+        assert!(glob_match(&["**", "web"], &["apps", "web"]));
+        assert!(glob_match(&["packages", "*-app"], &["packages", "my-app"]));
+        assert!(!glob_match(&["packages", "*-app"], &["packages", "lib"]));
+        assert!(glob_match(&["v?"], &["v1"]));
+    }
+
+    #[test]
+    fn should_use_root_without_workspaces() -> Result<(), Box<dyn std::error::Error>> {
+        let root = Path::new("test-assets/");
+
+        let (workspaces, missing) = discover_workspaces(root, &parse_package_json(root)?)?;
+
+        assert_eq!(workspaces, vec![ROOT_WORKSPACE.to_owned()]);
+        assert!(missing.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_report_missing_workspaces() -> Result<(), Box<dyn std::error::Error>> {
+        let root = Path::new("test-assets/monorepo/");
+
+        let (workspaces, missing) = discover_workspaces(root, &parse_package_json(root)?)?;
+
+        assert_eq!(workspaces, vec!["common/", "frontend/", "backend/"]);
+        assert_eq!(missing, vec!["tools/utils/"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_fail_when_no_workspace_exists() -> Result<(), Box<dyn std::error::Error>> {
+        let root = Path::new("test-assets/missing-workspaces/");
+
+        assert!(matches!(
+            discover_workspaces(root, &parse_package_json(root)?),
+            Err(DebsError::MissingWorkspaces(path)) if path == root.join("package.json")
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_pnpm_workspaces() -> Result<(), Box<dyn std::error::Error>> {
+        let root = Path::new("test-assets/pnpm/");
+
+        let (workspaces, missing) = discover_workspaces(root, &parse_package_json(root)?)?;
+
+        assert_eq!(workspaces, vec!["packages/common/", "packages/web/"]);
+        assert!(missing.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_expand_globs() -> Result<(), Box<dyn std::error::Error>> {
+        let root = Path::new("test-assets/workspace-globs/");
+
+        let (workspaces, missing) = discover_workspaces(root, &parse_package_json(root)?)?;
+
+        assert_eq!(
+            workspaces,
+            vec![
+                "packages/a/",
+                "packages/b/",
+                "apps/nested/api/",
+                "apps/web/"
+            ]
+        );
+        assert_eq!(missing, vec!["libs/*"]);
+
+        Ok(())
+    }
+}